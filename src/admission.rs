@@ -0,0 +1,174 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+/// What a [`Bus`](crate::bus::Bus) does with a new tag once it is at its lane
+/// limit.
+///
+/// See [`Mux::with_max_lanes`](crate::mux::Mux::with_max_lanes).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AtCapacity {
+    /// Refuse the new lane, failing with
+    /// [`MuxError::Refused`](crate::mux::MuxError::Refused).
+    #[default]
+    Reject,
+    /// Close the least recently used lane that has no values buffered with
+    /// [`CloseReason::Evicted`](crate::lane::CloseReason::Evicted) to make
+    /// room for the new lane, or refuse the new lane like
+    /// [`AtCapacity::Reject`] if every lane has values buffered.
+    EvictLru,
+}
+
+/// Counters of how often lane admission control kicked in.
+///
+/// See [`Mux::admission_stats`](crate::mux::Mux::admission_stats).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AdmissionStats {
+    /// The number of new lanes refused for being over the lane limit.
+    pub rejected: u64,
+    /// The number of new lanes refused for being over the creation rate.
+    pub throttled: u64,
+    /// The number of lanes evicted to make room for new ones.
+    pub evicted: u64,
+}
+
+/// The lane admission control of a [`Bus`](crate::bus::Bus).
+#[derive(Debug, Default)]
+pub(crate) struct Admission {
+    limit: Mutex<Option<Limit>>,
+    rate: Mutex<Option<Bucket>>,
+    rejected: AtomicU64,
+    throttled: AtomicU64,
+    evicted: AtomicU64,
+}
+
+/// The maximum number of concurrent lanes.
+#[derive(Debug)]
+struct Limit {
+    lanes: Arc<Semaphore>,
+    at_capacity: AtCapacity,
+}
+
+/// A token bucket limiting how fast lanes are created.
+#[derive(Debug)]
+struct Bucket {
+    burst: f64,
+    per_sec: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl Admission {
+    /// Sets the maximum number of concurrent lanes, and what to do once it is
+    /// reached.
+    #[inline]
+    pub(crate) fn set_max_lanes(&self, max: usize, at_capacity: AtCapacity) {
+        *lock(&self.limit) = Some(Limit {
+            lanes: Arc::new(Semaphore::new(max)),
+            at_capacity,
+        });
+    }
+
+    /// Sets how many lanes can be created in a burst, refilling at that many
+    /// lanes every `per`.
+    #[inline]
+    pub(crate) fn set_rate(&self, lanes: u32, per: Duration) {
+        let burst = f64::from(lanes);
+
+        *lock(&self.rate) = Some(Bucket {
+            burst,
+            per_sec: burst / per.as_secs_f64(),
+            tokens: burst,
+            last: Instant::now(),
+        });
+    }
+
+    /// Gets what to do with a new lane once the lane limit is reached.
+    #[inline]
+    pub(crate) fn at_capacity(&self) -> AtCapacity {
+        lock(&self.limit)
+            .as_ref()
+            .map_or(AtCapacity::Reject, |limit| limit.at_capacity)
+    }
+
+    /// Takes a token for a new lane from the rate limit, if any.
+    ///
+    /// # Returns
+    /// Whether the lane is within the rate limit.
+    pub(crate) fn throttle(&self) -> bool {
+        let mut rate = lock(&self.rate);
+        let Some(ref mut bucket) = *rate else {
+            return true;
+        };
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last).as_secs_f64();
+
+        bucket.tokens =
+            (bucket.tokens + elapsed * bucket.per_sec).min(bucket.burst);
+        bucket.last = now;
+
+        if bucket.tokens < 1.0 {
+            self.throttled.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+
+        bucket.tokens -= 1.0;
+        true
+    }
+
+    /// Gives back a token taken by [`Admission::throttle`] for a lane that was
+    /// refused nonetheless.
+    #[inline]
+    pub(crate) fn refund(&self) {
+        if let Some(ref mut bucket) = *lock(&self.rate) {
+            bucket.tokens = (bucket.tokens + 1.0).min(bucket.burst);
+        }
+    }
+
+    /// Reserves room for a new lane under the lane limit, if any.
+    ///
+    /// # Returns
+    /// * [`Ok(Some(permit))`] - The reservation, which is released once
+    ///   dropped.
+    /// * [`Ok(None)`] - If there is no lane limit.
+    /// * [`Err(())`] - If the lane limit is reached.
+    #[inline]
+    pub(crate) fn reserve(&self) -> Result<Option<OwnedSemaphorePermit>, ()> {
+        let lanes = match *lock(&self.limit) {
+            | Some(ref limit) => limit.lanes.clone(),
+            | None => return Ok(None),
+        };
+
+        lanes.try_acquire_owned().map(Some).map_err(|_| ())
+    }
+
+    /// Records a new lane that was refused for being over the lane limit.
+    #[inline]
+    pub(crate) fn rejected(&self) {
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a lane that was evicted to make room for a new one.
+    #[inline]
+    pub(crate) fn evicted(&self) {
+        self.evicted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Gets the admission counters so far.
+    #[inline]
+    pub(crate) fn stats(&self) -> AdmissionStats {
+        AdmissionStats {
+            rejected: self.rejected.load(Ordering::Relaxed),
+            throttled: self.throttled.load(Ordering::Relaxed),
+            evicted: self.evicted.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[inline]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
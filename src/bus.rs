@@ -1,7 +1,185 @@
-use tokio::sync::mpsc;
-use tokio::sync::mpsc::error::SendError;
+use std::ops::ControlFlow;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use crate::{Key, LaneRx, LaneTx, Map};
+use dashmap::DashMap;
+use tokio::sync::mpsc::error::{SendError, SendTimeoutError, TrySendError};
+use tokio::sync::{mpsc, watch, OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+use crate::admission::{Admission, AdmissionStats, AtCapacity};
+use crate::event::{Events, LaneEvents, DEFAULT_EVENTS_BUF};
+use crate::lane::{
+    CloseReason,
+    Feed,
+    Hooks,
+    Inlet,
+    LaneTxSlot,
+    Lifecycle,
+    Signal,
+    Tracker,
+};
+use crate::overflow::ParkBudget;
+use crate::stats::{Counters, Meter, MuxStats};
+use crate::{Key, LaneRx, LaneTx, Map, MuxError, OpenError, Overflow};
+
+/// The default number of times [`Bus::push`] retries after hitting a closed
+/// lane.
+pub const DEFAULT_RETRIES: usize = 3;
+
+/// The default number of values that can be parked with [`Overflow::Park`]
+/// across all lanes of a [`Bus`].
+pub const DEFAULT_PARK_BUDGET: usize = 1024;
+
+/// The outcome of a successful [`Bus::push`] or
+/// [`Mux::send`](crate::mux::Mux::send).
+#[derive(Debug)]
+pub enum Delivery<L> {
+    /// The value was delivered to an existing lane.
+    Existing,
+    /// The value was delivered to a new lane, which is returned.
+    New(L),
+    /// The value was delivered to a new lane, which was handed to
+    /// [`Incoming`](crate::incoming::Incoming).
+    Accepted,
+    /// The lane was full, so the value was dropped as per the lane's
+    /// [`Overflow::DropNewest`] policy.
+    Dropped,
+}
+
+impl<L> Delivery<L> {
+    /// Gets the new lane the value was delivered to, if any.
+    ///
+    /// # Returns
+    /// * [`Some(lane)`] - If the value was delivered to a new lane that was
+    ///   returned.
+    /// * [`None`] - Otherwise.
+    #[inline]
+    pub fn lane(self) -> Option<L> {
+        match self {
+            | Delivery::New(lane) => Some(lane),
+            | Delivery::Existing | Delivery::Accepted | Delivery::Dropped => {
+                None
+            }
+        }
+    }
+
+    /// Splits off the new lane the value was delivered to.
+    ///
+    /// # Returns
+    /// * [`Ok(lane)`] - If the value was delivered to a new lane.
+    /// * [`Err(delivery)`] - The delivery otherwise, which holds no lane.
+    #[inline]
+    pub(crate) fn into_lane<U>(self) -> Result<L, Delivery<U>> {
+        match self {
+            | Delivery::New(lane) => Ok(lane),
+            | Delivery::Existing => Err(Delivery::Existing),
+            | Delivery::Accepted => Err(Delivery::Accepted),
+            | Delivery::Dropped => Err(Delivery::Dropped),
+        }
+    }
+}
+
+/// The outcome of pushing a single value to many lanes at once, for each of
+/// the lanes.
+///
+/// See [`Bus::broadcast`] and [`Bus::multicast`].
+#[derive(Debug)]
+pub struct DeliveryReport<T, V> {
+    /// The tags of the lanes the value was pushed to.
+    pub delivered: Vec<T>,
+    /// The tags of the lanes that were full, and dropped the value as per
+    /// their [`Overflow::DropNewest`] policy.
+    pub dropped: Vec<T>,
+    /// Why the value could not be pushed to the remaining lanes, each along
+    /// with the tag of the lane and the undelivered copy of the value, where
+    /// [`MuxError::Rejected`] stands for a lane that does not exist or was
+    /// closed.
+    pub failed: Vec<MuxError<T, V>>,
+}
+
+impl<T, V> Default for DeliveryReport<T, V> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            delivered: Vec::new(),
+            dropped: Vec::new(),
+            failed: Vec::new(),
+        }
+    }
+}
+
+impl<T, V> DeliveryReport<T, V> {
+    /// Gets whether the value was pushed to all lanes or not.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.dropped.is_empty() && self.failed.is_empty()
+    }
+}
+
+/// A handle to push values to an existing lane of a [`Bus`] directly, without
+/// looking the lane up by its tag on every push.
+///
+/// A route is tied to the lane it was made for, so once that lane is closed,
+/// pushing through the route fails, even if a new lane with the same tag was
+/// created since.
+///
+/// See [`Bus::route_to`].
+#[derive(Debug)]
+pub struct Route<T: Key, V>(Feed<T, V>);
+
+impl<T: Key, V> Clone for Route<T, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Key, V> Route<T, V> {
+    /// Gets the tag of the lane.
+    #[inline]
+    pub const fn tag(&self) -> &T {
+        self.0.tag()
+    }
+
+    /// Gets whether the lane is closed or not.
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+
+    /// Pushes a value to the lane, as per the lane's overflow policy.
+    ///
+    /// # Parameters
+    /// * `value` - The value to push.
+    ///
+    /// # Returns
+    /// * [`Ok(true)`] - If the value was pushed.
+    /// * [`Ok(false)`] - If the lane was full, and its overflow policy dropped
+    ///   the value.
+    /// * [`Err(MuxError::Rejected)`] - If the lane is closed.
+    /// * [`Err(MuxError)`] - If the value could not be pushed otherwise, along
+    ///   with the tag and value.
+    #[inline]
+    pub async fn push(&mut self, value: V) -> Result<bool, MuxError<T, V>> {
+        Bus::feed(&mut self.0, value).await
+    }
+
+    /// Attempts to push a value to the lane without waiting.
+    ///
+    /// See [`Route::push`].
+    ///
+    /// # Parameters
+    /// * `value` - The value to push.
+    ///
+    /// # Returns
+    /// Same as [`Route::push`], or [`Err(MuxError::Full)`] if the lane is
+    /// full.
+    #[inline]
+    pub fn try_push(&mut self, value: V) -> Result<bool, MuxError<T, V>> {
+        Bus::try_feed(&mut self.0, value)
+    }
+}
 
 /// The backing bus for [`Mux`](crate::mux::Mux).
 ///
@@ -9,8 +187,28 @@ use crate::{Key, LaneRx, LaneTx, Map};
 /// distributing messages to the appropriate lanes.
 #[derive(Debug)]
 pub struct Bus<T: Key, V> {
-    inner: Map<T, LaneTx<T, V>>,
+    inner: Map<T, Inlet<T, V>>,
     lane_buf: usize,
+    hooks: Option<Hooks<T, V>>,
+    aborted: watch::Sender<bool>,
+    retries: usize,
+    overflow: Overflow,
+    overflows: DashMap<T, Overflow>,
+    budget: Arc<ParkBudget>,
+    idle: Mutex<Option<Duration>>,
+    idles: DashMap<T, Duration>,
+    sweep: Mutex<Option<Sweep>>,
+    admission: Admission,
+    totals: Arc<Counters>,
+    events: Events<T>,
+}
+
+/// When idle lanes are next looked for, which is done as often as the
+/// shortest idle timeout set on a [`Bus`].
+#[derive(Debug, Clone, Copy)]
+struct Sweep {
+    period: Duration,
+    due: Instant,
 }
 
 impl<T: Key, V> Bus<T, V> {
@@ -23,65 +221,957 @@ impl<T: Key, V> Bus<T, V> {
         Self {
             inner: Default::default(),
             lane_buf,
+            hooks: None,
+            aborted: watch::Sender::new(false),
+            retries: DEFAULT_RETRIES,
+            overflow: Overflow::Block,
+            overflows: DashMap::new(),
+            budget: Arc::new(ParkBudget::new(DEFAULT_PARK_BUDGET)),
+            idle: Mutex::new(None),
+            idles: DashMap::new(),
+            sweep: Mutex::new(None),
+            admission: Default::default(),
+            totals: Default::default(),
+            events: Events::new(DEFAULT_EVENTS_BUF),
+        }
+    }
+
+    /// Create a new instance of [`Bus`] whose lane receivers are controlled
+    /// by `hooks`.
+    ///
+    /// # Parameters
+    /// * `lane_buf` - The buffer size for each lane.
+    /// * `hooks` - The hooks controlling the lanes.
+    #[cfg(feature = "transport")]
+    #[inline]
+    pub(crate) fn with_hooks(lane_buf: usize, hooks: Hooks<T, V>) -> Self {
+        Self {
+            inner: Default::default(),
+            lane_buf,
+            hooks: Some(hooks),
+            aborted: watch::Sender::new(false),
+            retries: DEFAULT_RETRIES,
+            overflow: Overflow::Block,
+            overflows: DashMap::new(),
+            budget: Arc::new(ParkBudget::new(DEFAULT_PARK_BUDGET)),
+            idle: Mutex::new(None),
+            idles: DashMap::new(),
+            sweep: Mutex::new(None),
+            admission: Default::default(),
+            totals: Default::default(),
+            events: Events::new(DEFAULT_EVENTS_BUF),
+        }
+    }
+
+    /// Sets how many times [`Bus::push`] retries after hitting a closed lane,
+    /// before giving up.
+    ///
+    /// # Parameters
+    /// * `retries` - The retry budget, [`DEFAULT_RETRIES`] by default.
+    #[inline]
+    pub fn set_retries(&mut self, retries: usize) {
+        self.retries = retries;
+    }
+
+    /// Sets the overflow policy of lanes created from now on.
+    ///
+    /// # Parameters
+    /// * `overflow` - The overflow policy, [`Overflow::Block`] by default.
+    #[inline]
+    pub fn set_overflow(&mut self, overflow: Overflow) {
+        self.overflow = overflow;
+    }
+
+    /// Sets the overflow policy of the lane with the given tag, overriding
+    /// the one set with [`Bus::set_overflow`].
+    ///
+    /// This takes effect once the lane is created, so a lane that already
+    /// exists keeps its policy until it is closed.
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    /// * `overflow` - The overflow policy.
+    #[inline]
+    pub fn set_lane_overflow(&self, tag: T, overflow: Overflow) {
+        self.overflows.insert(tag, overflow);
+    }
+
+    /// Sets how many values can be parked with [`Overflow::Park`] across all
+    /// lanes.
+    ///
+    /// Values parked so far count against the new budget, so shrinking it
+    /// below their number prevents parking more values until enough of them
+    /// are received.
+    ///
+    /// # Parameters
+    /// * `budget` - The park budget, [`DEFAULT_PARK_BUDGET`] by default.
+    #[inline]
+    pub fn set_park_budget(&self, budget: usize) {
+        self.budget.resize(budget);
+    }
+
+    /// Sets how long lanes created from now on can see no traffic in either
+    /// direction before they are closed with [`CloseReason::Idle`].
+    ///
+    /// # Parameters
+    /// * `timeout` - The idle timeout, or [`None`] to never close idle lanes,
+    ///   which is the default.
+    #[inline]
+    pub fn set_idle_timeout(&self, timeout: Option<Duration>) {
+        *self.idle.lock().unwrap_or_else(|err| err.into_inner()) = timeout;
+
+        if let Some(timeout) = timeout {
+            self.schedule_sweep(timeout);
+        }
+    }
+
+    /// Sets the idle timeout of the lane with the given tag, overriding the
+    /// one set with [`Bus::set_idle_timeout`].
+    ///
+    /// This takes effect once the lane is created, so a lane that already
+    /// exists keeps its idle timeout until it is closed.
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    /// * `timeout` - The idle timeout.
+    #[inline]
+    pub fn set_lane_idle_timeout(&self, tag: T, timeout: Duration) {
+        self.idles.insert(tag, timeout);
+        self.schedule_sweep(timeout);
+    }
+
+    /// Sets the maximum number of lanes that can be open at once, and what
+    /// creating a new lane does once it is reached.
+    ///
+    /// Lanes that already exist do not count towards the limit.
+    ///
+    /// # Parameters
+    /// * `max` - The maximum number of lanes. Lanes are unlimited by default.
+    /// * `at_capacity` - What to do with new lanes over the limit.
+    #[inline]
+    pub fn set_max_lanes(&self, max: usize, at_capacity: AtCapacity) {
+        self.admission.set_max_lanes(max, at_capacity);
+    }
+
+    /// Sets how fast new lanes can be created, as a token bucket that holds up
+    /// to `lanes` lanes, and refills at that many lanes every `per`.
+    ///
+    /// # Parameters
+    /// * `lanes` - The number of lanes that can be created in a burst.
+    /// * `per` - How long the bucket takes to refill. Lane creation is not rate
+    ///   limited by default.
+    #[inline]
+    pub fn set_lane_rate(&self, lanes: u32, per: Duration) {
+        self.admission.set_rate(lanes, per);
+    }
+
+    /// Gets how often lane admission control kicked in so far.
+    #[inline]
+    pub fn admission_stats(&self) -> AdmissionStats {
+        self.admission.stats()
+    }
+
+    /// Sets how many lane events are buffered for each observer subscribed
+    /// from now on, past which slow observers miss the oldest ones.
+    ///
+    /// # Parameters
+    /// * `buf` - The buffer size, [`DEFAULT_EVENTS_BUF`] by default.
+    #[inline]
+    pub fn set_events_buf(&mut self, buf: usize) {
+        self.events = Events::new(buf.max(1));
+    }
+
+    /// Subscribes to the lifecycle events of the lanes of the bus.
+    ///
+    /// Each subscriber sees every event emitted from now on, independently of
+    /// any other subscriber.
+    #[inline]
+    pub fn events(&self) -> LaneEvents<T> {
+        LaneEvents::new(self.events.subscribe())
+    }
+
+    /// Gets a snapshot of the statistics of the bus and its open lanes.
+    pub fn stats(&self) -> MuxStats<T> {
+        let lanes = self
+            .inner
+            .iter()
+            .map(|lane| (lane.key().clone(), lane.stats()))
+            .collect();
+
+        MuxStats {
+            lanes,
+            totals: self.totals.snapshot(),
+            admission: self.admission.stats(),
         }
     }
 
-    /// Gets a reference to the lane with the given tag.
+    /// Sends a value to the lane with the given tag.
     ///
-    /// A new lane will be created if one does not already exist.
+    /// A new lane will be created if one does not already exist. A lane whose
+    /// receiver was closed is replaced by a new one, up to the retry budget
+    /// (see [`Bus::set_retries`]).
     ///
     /// # Parameters
     /// * `tag` - The tag of the lane.
     /// * `value` - The value to send to the lane.
-    pub async fn push(&self, mut tag: T, mut value: V) -> Option<LaneRx<T, V>> {
-        loop {
-            match self.push_item(tag, value).await {
-                | Ok(rx) => return rx,
-                | Err(SendError((etag, evalue))) => {
-                    (tag, value) = (etag, evalue);
-                }
+    ///
+    /// # Returns
+    /// * [`Ok(Delivery::Existing)`] - If the value was sent to an existing
+    ///   lane.
+    /// * [`Ok(Delivery::New(rx))`] - If the value was sent to a new lane.
+    /// * [`Ok(Delivery::Dropped)`] - If the lane was full, and its overflow
+    ///   policy dropped the value.
+    /// * [`Err(MuxError::Refused)`] - If a new lane was needed, but refused by
+    ///   the lane limit or creation rate.
+    /// * [`Err(MuxError)`] - If the value could not be delivered otherwise,
+    ///   along with the undelivered tag and value.
+    pub async fn push(
+        &self,
+        mut tag: T,
+        mut value: V,
+    ) -> Result<Delivery<LaneRx<T, V>>, MuxError<T, V>> {
+        for _ in 0..=self.retries {
+            if *self.aborted.borrow() {
+                return Err(MuxError::Shutdown(tag, value));
+            }
+
+            let Ok(routed) = self.route(&tag) else {
+                return Err(MuxError::Refused(tag, value));
+            };
+            let Some((mut feed, lane_rx)) = routed else {
+                continue;
+            };
+            let pushed = Self::feed(&mut feed, value).await;
+
+            match Self::settle(pushed, lane_rx) {
+                | ControlFlow::Break(delivery) => return delivery,
+                | ControlFlow::Continue(item) => (tag, value) = item,
             }
         }
+
+        Err(MuxError::Exhausted(tag, value))
     }
 
-    /// Closes all lanes.
-    #[inline]
-    pub fn clear(&mut self) {
-        self.inner.clear();
+    /// Attempts to send a value to the lane with the given tag without
+    /// waiting.
+    ///
+    /// See [`Bus::push`].
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    /// * `value` - The value to send to the lane.
+    ///
+    /// # Returns
+    /// Same as [`Bus::push`], or [`Err(MuxError::Full)`] if the lane is full.
+    pub fn try_push(
+        &self,
+        mut tag: T,
+        mut value: V,
+    ) -> Result<Delivery<LaneRx<T, V>>, MuxError<T, V>> {
+        for _ in 0..=self.retries {
+            if *self.aborted.borrow() {
+                return Err(MuxError::Shutdown(tag, value));
+            }
+
+            let Ok(routed) = self.route(&tag) else {
+                return Err(MuxError::Refused(tag, value));
+            };
+            let Some((mut feed, lane_rx)) = routed else {
+                continue;
+            };
+            let pushed = Self::try_feed(&mut feed, value);
+
+            match Self::settle(pushed, lane_rx) {
+                | ControlFlow::Break(delivery) => return delivery,
+                | ControlFlow::Continue(item) => (tag, value) = item,
+            }
+        }
+
+        Err(MuxError::Exhausted(tag, value))
+    }
+
+    /// Sends a value to the lane with the given tag, giving up once `timeout`
+    /// elapses.
+    ///
+    /// See [`Bus::push`].
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    /// * `value` - The value to send to the lane.
+    /// * `timeout` - How long to wait for room in the lane.
+    ///
+    /// # Returns
+    /// Same as [`Bus::push`], or [`Err(MuxError::Timeout)`] if the timeout
+    /// elapsed.
+    pub async fn push_timeout(
+        &self,
+        mut tag: T,
+        mut value: V,
+        timeout: Duration,
+    ) -> Result<Delivery<LaneRx<T, V>>, MuxError<T, V>> {
+        let deadline = Instant::now() + timeout;
+
+        for _ in 0..=self.retries {
+            if *self.aborted.borrow() {
+                return Err(MuxError::Shutdown(tag, value));
+            }
+
+            let Ok(routed) = self.route(&tag) else {
+                return Err(MuxError::Refused(tag, value));
+            };
+            let Some((mut feed, lane_rx)) = routed else {
+                continue;
+            };
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let pushed = match feed.overflow() {
+                | Overflow::Block => feed
+                    .send_timeout(value, timeout)
+                    .await
+                    .map(|()| true)
+                    .map_err(Self::timed_out),
+                | Overflow::Park => feed
+                    .park(value, Some(deadline))
+                    .await
+                    .map(|()| true)
+                    .map_err(Self::timed_out),
+                | _ => Self::try_feed(&mut feed, value),
+            };
+
+            match Self::settle(pushed, lane_rx) {
+                | ControlFlow::Break(delivery) => return delivery,
+                | ControlFlow::Continue(item) => (tag, value) = item,
+            }
+        }
+
+        Err(MuxError::Exhausted(tag, value))
     }
 
+    /// Pushes a copy of a value to every open lane, as per each lane's
+    /// overflow policy.
+    ///
+    /// Lanes are pushed to one after another, so a full lane whose policy is
+    /// [`Overflow::Block`] holds up the lanes after it. Values that are
+    /// expensive to clone can be wrapped in an [`Arc`] to be shared instead.
+    ///
+    /// # Parameters
+    /// * `value` - The value to push to the lanes.
+    ///
+    /// # Returns
+    /// The outcome of pushing the value to each lane.
     #[inline]
-    async fn push_item(
+    pub async fn broadcast(&self, value: V) -> DeliveryReport<T, V>
+    where
+        V: Clone,
+    {
+        self.multicast_where(|_| true, value).await
+    }
+
+    /// Pushes a copy of a value to the open lanes with the given tags, as per
+    /// each lane's overflow policy.
+    ///
+    /// No new lanes are created, so tags of lanes that are not open are
+    /// reported as failed. See [`Bus::broadcast`].
+    ///
+    /// # Parameters
+    /// * `tags` - The tags of the lanes.
+    /// * `value` - The value to push to the lanes.
+    ///
+    /// # Returns
+    /// The outcome of pushing the value to each lane.
+    pub async fn multicast<I>(&self, tags: I, value: V) -> DeliveryReport<T, V>
+    where
+        I: IntoIterator<Item = T>,
+        V: Clone,
+    {
+        self.sweep_idle();
+
+        let targets = tags
+            .into_iter()
+            .map(|tag| {
+                let feed =
+                    self.inner.get_mut(&tag).map(|inlet| inlet.feed().clone());
+
+                (tag, feed)
+            })
+            .collect();
+
+        Self::fan_out(targets, value).await
+    }
+
+    /// Pushes a copy of a value to the open lanes whose tags match a
+    /// predicate, as per each lane's overflow policy.
+    ///
+    /// See [`Bus::broadcast`].
+    ///
+    /// # Parameters
+    /// * `predicate` - Whether to push to the lane with the given tag.
+    /// * `value` - The value to push to the lanes.
+    ///
+    /// # Returns
+    /// The outcome of pushing the value to each lane.
+    pub async fn multicast_where<F>(
         &self,
-        tag: T,
+        mut predicate: F,
         value: V,
-    ) -> Result<Option<LaneRx<T, V>>, SendError<(T, V)>> {
+    ) -> DeliveryReport<T, V>
+    where
+        F: FnMut(&T) -> bool,
+        V: Clone,
+    {
+        self.sweep_idle();
+
+        // the feeds are cloned so that the map is not locked while sending
+        let targets = self
+            .inner
+            .iter()
+            .filter(|lane| predicate(lane.key()))
+            .map(|lane| (lane.key().clone(), Some(lane.feed().clone())))
+            .collect();
+
+        Self::fan_out(targets, value).await
+    }
+
+    /// Creates a new lane with the given tag, without sending any value to it.
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    ///
+    /// # Returns
+    /// * [`Ok(LaneRx)`] - The receiver of the new lane.
+    /// * [`Err(OpenError::InUse)`] - If a lane with the given tag already
+    ///   exists.
+    /// * [`Err(OpenError::Refused)`] - If the new lane was refused by the lane
+    ///   limit or creation rate.
+    pub fn open(&self, tag: T) -> Result<LaneRx<T, V>, OpenError<T>> {
+        self.sweep_idle();
+
+        if self.inner.get_mut(&tag).is_some() {
+            return Err(OpenError::InUse(tag));
+        }
+
+        let Ok(permit) = self.admit() else {
+            return Err(OpenError::Refused(tag));
+        };
         let mut lane_rx = None;
-        let mut tx = self.inner.get_or_insert(tag.clone(), |slot| {
-            let (tx, rx) = mpsc::channel(self.lane_buf);
 
-            lane_rx = Some(LaneRx::new(rx, slot));
+        self.inner.get_or_insert(tag.clone(), |slot| {
+            self.create(slot, &mut lane_rx).admitted(permit)
+        });
+
+        lane_rx.ok_or(OpenError::InUse(tag))
+    }
+
+    /// Attempts to send a value to the lane with the given tag without
+    /// waiting, only if it already exists, as per the lane's overflow policy.
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    /// * `value` - The value to send to the lane.
+    ///
+    /// # Returns
+    /// * [`Ok(true)`] - If the value was pushed.
+    /// * [`Ok(false)`] - If the lane was full, and its overflow policy dropped
+    ///   the value.
+    /// * [`Err(MuxError::Full)`] - If the lane is full.
+    /// * [`Err(MuxError::Rejected)`] - If no lane with the given tag exists.
+    #[cfg(feature = "transport")]
+    pub(crate) fn try_push_existing(
+        &self,
+        tag: T,
+        value: V,
+    ) -> Result<bool, MuxError<T, V>> {
+        self.sweep_idle();
+
+        let feed = self.inner.get_mut(&tag).map(|inlet| inlet.feed().clone());
+
+        match feed {
+            | Some(mut feed) => Self::try_feed(&mut feed, value),
+            | None => Err(MuxError::Rejected(tag, value)),
+        }
+    }
+
+    /// Gets a route to the existing lane with the given tag, through which
+    /// values can be pushed to the lane without looking it up again.
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    ///
+    /// # Returns
+    /// * [`Some(route)`] - The route to the lane.
+    /// * [`None`] - If no lane with the given tag exists.
+    #[inline]
+    pub fn route_to(&self, tag: &T) -> Option<Route<T, V>> {
+        let feed = self.inner.get_mut(tag)?.feed().clone();
+
+        (!feed.is_closed()).then_some(Route(feed))
+    }
+
+    /// Makes a new sender for the existing lane with the given tag.
+    ///
+    /// # Returns
+    /// * [`Some(tx)`] - The new sender.
+    /// * [`None`] - If no lane with the given tag exists, or if all of its
+    ///   senders were dropped.
+    #[inline]
+    pub(crate) fn sender(&self, tag: &T) -> Option<LaneTx<T, V>> {
+        let tx = self.inner.get_mut(tag)?.sender()?;
+
+        Some(tx.tracked(self.track()))
+    }
+
+    /// Closes the lane with the given tag, if any.
+    ///
+    /// The lane's receiver can still receive the values that were already
+    /// pushed to it.
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    ///
+    /// # Returns
+    /// Whether a lane with the given tag was found or not.
+    #[inline]
+    pub fn close(&self, tag: &T) -> bool {
+        self.close_with(tag, CloseReason::Graceful)
+    }
+
+    /// Closes the lane with the given tag, if any, for the given reason.
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    /// * `reason` - The reason reported to the lane's receiver.
+    ///
+    /// # Returns
+    /// Whether a lane with the given tag was found or not.
+    #[inline]
+    pub fn close_with(&self, tag: &T, reason: CloseReason) -> bool {
+        match self.inner.remove(tag) {
+            | Some((_, inlet)) => {
+                Self::close_inlet(&inlet, reason);
+                true
+            }
+            | None => false,
+        }
+    }
+
+    /// Closes all lanes.
+    #[inline]
+    pub fn clear(&self) {
+        self.clear_with(CloseReason::Shutdown);
+    }
 
-            LaneTx::new(tx, tag.clone())
+    /// Closes all lanes for the given reason.
+    ///
+    /// # Parameters
+    /// * `reason` - The reason reported to the lanes' receivers.
+    #[inline]
+    pub fn clear_with(&self, reason: CloseReason) {
+        self.inner.retain(|_, inlet| {
+            Self::close_inlet(inlet, reason.clone());
+            false
         });
+    }
 
-        match lane_rx {
-            | Some(lane_rx) => {
-                tx.send(value).await.unwrap();
-                Ok(Some(lane_rx))
+    /// Closes lanes that saw no traffic in either direction for their idle
+    /// timeout, discarding any values buffered in them.
+    ///
+    /// Lanes whose receivers are waiting for values are closed as soon as
+    /// they become idle anyway, while lanes whose receivers are left
+    /// unattended are looked for whenever values are pushed or lanes are
+    /// opened, at most once per the shortest idle timeout. This closes them
+    /// right away instead.
+    ///
+    /// # Returns
+    /// The number of closed lanes.
+    pub fn evict_idle(&self) -> usize {
+        let now = Instant::now();
+        let mut evicted = 0;
+
+        self.inner.retain(|tag, inlet| {
+            if !inlet.is_idle(now) {
+                return true;
             }
+
+            self.evict(tag, inlet, CloseReason::Idle);
+            inlet.discard();
+            evicted += 1;
+            false
+        });
+
+        evicted
+    }
+
+    /// Closes all lanes, and makes any lane handles given out so far fail
+    /// immediately, discarding any values buffered in them.
+    #[inline]
+    pub fn abort(&self) {
+        self.aborted.send_replace(true);
+        self.clear();
+    }
+
+    /// Waits until all lane handles given out so far are dropped.
+    #[inline]
+    pub async fn finished(&self) {
+        self.aborted.closed().await;
+    }
+
+    /// Creates a new tracker for a lane handle given out by this bus.
+    #[inline]
+    pub(crate) fn track(&self) -> Tracker {
+        Tracker::new(self.aborted.subscribe())
+    }
+
+    /// Gets the feed of the lane with the given tag, creating the lane if it
+    /// does not exist.
+    ///
+    /// # Returns
+    /// * [`Ok(Some((feed, rx)))`] - The feed of the lane, along with the
+    ///   receiver of the lane if it was just created.
+    /// * [`Ok(None)`] - If the lane was closed, in which case it is removed so
+    ///   that the next attempt creates a new lane.
+    /// * [`Err(())`] - If the lane did not exist, and creating it was refused
+    ///   by admission control.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn route(
+        &self,
+        tag: &T,
+    ) -> Result<Option<(Feed<T, V>, Option<LaneRx<T, V>>)>, ()> {
+        self.sweep_idle();
+
+        // the feed is cloned so that the map is not locked while sending
+        let existing =
+            self.inner.get_mut(tag).map(|inlet| inlet.feed().clone());
+        let mut lane_rx = None;
+        let feed = match existing {
+            | Some(feed) => feed,
             | None => {
-                if tx.is_closed() {
-                    // remove closed lane from map
-                    _ = self.inner.remove(&tag);
+                let permit = self.admit()?;
+
+                self.inner
+                    .get_or_insert(tag.clone(), |slot| {
+                        self.create(slot, &mut lane_rx).admitted(permit)
+                    })
+                    .feed()
+                    .clone()
+            }
+        };
+
+        if lane_rx.is_none() && feed.is_closed() {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(parent: feed.span(), "lane was closed, retrying");
+
+            // remove closed lane from map
+            _ = self.inner.remove(tag);
+
+            return Ok(None);
+        }
+
+        Ok(Some((feed, lane_rx)))
+    }
+
+    /// Makes idle lanes looked for at least once per `timeout`.
+    ///
+    /// # Parameters
+    /// * `timeout` - An idle timeout set on the bus or on one of its lanes.
+    fn schedule_sweep(&self, timeout: Duration) {
+        let due = Instant::now() + timeout;
+        let mut sweep =
+            self.sweep.lock().unwrap_or_else(|err| err.into_inner());
+
+        *sweep = Some(match *sweep {
+            | Some(sweep) => Sweep {
+                period: sweep.period.min(timeout),
+                due: sweep.due.min(due),
+            },
+            | None => Sweep {
+                period: timeout,
+                due,
+            },
+        });
+    }
+
+    /// Closes idle lanes if they were last looked for a sweep period ago, so
+    /// that lanes whose receivers are left unattended are reclaimed by the
+    /// traffic of the others.
+    #[inline]
+    fn sweep_idle(&self) {
+        let now = Instant::now();
+        let mut sweep =
+            self.sweep.lock().unwrap_or_else(|err| err.into_inner());
+        let Some(ref mut next) = *sweep else {
+            return;
+        };
+
+        if next.due > now {
+            return;
+        }
 
-                    return Err(SendError((tag, value)));
-                }
+        next.due = now + next.period;
+        // the map is not scanned while holding the lock
+        drop(sweep);
+        self.evict_idle();
+    }
+
+    /// Admits a new lane under the lane limit and creation rate, evicting the
+    /// least recently used idle lane to make room for it if need be.
+    ///
+    /// # Returns
+    /// * [`Ok(permit)`] - The lane's reservation under the lane limit, if any.
+    /// * [`Err(())`] - If the lane was refused.
+    fn admit(&self) -> Result<Option<OwnedSemaphorePermit>, ()> {
+        if !self.admission.throttle() {
+            return Err(());
+        }
+
+        loop {
+            if let Ok(permit) = self.admission.reserve() {
+                return Ok(permit);
+            }
 
-                tx.send(value).await.map(|_| None)
+            if self.admission.at_capacity() != AtCapacity::EvictLru
+                || !self.evict_lru()
+            {
+                // the lane was never created, so it does not count against
+                // the creation rate
+                self.admission.refund();
+                self.admission.rejected();
+                return Err(());
             }
         }
     }
+
+    /// Closes the least recently used lane that has no values buffered or
+    /// parked with [`CloseReason::Evicted`].
+    ///
+    /// This scans all lanes of the bus, which is only done once the lane limit
+    /// is reached.
+    ///
+    /// # Returns
+    /// Whether a lane was found to evict or not.
+    fn evict_lru(&self) -> bool {
+        let lru = self
+            .inner
+            .iter()
+            .filter(|lane| lane.is_drained())
+            .map(|lane| (lane.last_seen(), lane.key().clone()))
+            .min_by_key(|(last_seen, _)| *last_seen);
+        let Some((_, tag)) = lru else {
+            return false;
+        };
+
+        // the lane might have been closed meanwhile, which makes room anyway,
+        // or have received values, which keeps it
+        if let Some((tag, inlet)) =
+            self.inner.remove_if(&tag, |_, inlet| inlet.is_drained())
+        {
+            self.evict(&tag, &inlet, CloseReason::Evicted);
+            self.admission.evicted();
+        }
+
+        true
+    }
+
+    /// Closes an evicted lane, resetting it on the remote end, if any.
+    #[inline]
+    fn evict(&self, tag: &T, inlet: &Inlet<T, V>, reason: CloseReason) {
+        Self::close_inlet(inlet, reason);
+
+        if let Some(ref hooks) = self.hooks {
+            hooks.notify(Signal::RxClosed(tag.clone()));
+        }
+    }
+
+    /// Closes a lane removed from the bus for the given reason.
+    #[inline]
+    fn close_inlet(inlet: &Inlet<T, V>, reason: CloseReason) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(parent: inlet.feed().span(), ?reason, "lane closed");
+
+        inlet.set_reason(reason);
+        inlet.close();
+    }
+
+    /// Pushes a copy of a value through each of the given feeds.
+    ///
+    /// # Parameters
+    /// * `targets` - The tag of each lane, along with its feed if it is open.
+    /// * `value` - The value to push, which the last lane gets rather than a
+    ///   copy.
+    async fn fan_out(
+        targets: Vec<(T, Option<Feed<T, V>>)>,
+        value: V,
+    ) -> DeliveryReport<T, V>
+    where
+        V: Clone,
+    {
+        let mut report = DeliveryReport::default();
+        let count = targets.len();
+        let mut value = Some(value);
+
+        for (idx, (tag, feed)) in targets.into_iter().enumerate() {
+            let copy = if idx + 1 < count {
+                value.clone()
+            } else {
+                value.take()
+            };
+            let copy = copy.expect("the value to be taken last");
+            let pushed = match feed {
+                | Some(mut feed) => Self::feed(&mut feed, copy).await,
+                | None => Err(MuxError::Rejected(tag.clone(), copy)),
+            };
+
+            match pushed {
+                | Ok(true) => report.delivered.push(tag),
+                | Ok(false) => report.dropped.push(tag),
+                | Err(err) => report.failed.push(err),
+            }
+        }
+
+        report
+    }
+
+    /// Pushes a value through a feed, as per its overflow policy.
+    ///
+    /// # Returns
+    /// * [`Ok(true)`] - If the value was pushed.
+    /// * [`Ok(false)`] - If the value was dropped.
+    /// * [`Err(MuxError)`] - If the value was not pushed, where
+    ///   [`MuxError::Rejected`] stands for a closed lane.
+    #[inline]
+    async fn feed(
+        feed: &mut Feed<T, V>,
+        value: V,
+    ) -> Result<bool, MuxError<T, V>> {
+        match feed.overflow() {
+            | Overflow::Block => feed.send(value).await.map(|()| true).map_err(
+                |SendError((tag, value))| MuxError::Rejected(tag, value),
+            ),
+            | Overflow::Park => feed
+                .park(value, None)
+                .await
+                .map(|()| true)
+                .map_err(Self::timed_out),
+            | _ => Self::try_feed(feed, value),
+        }
+    }
+
+    /// Pushes a value through a feed without waiting, as per its overflow
+    /// policy.
+    #[inline]
+    fn try_feed(
+        feed: &mut Feed<T, V>,
+        value: V,
+    ) -> Result<bool, MuxError<T, V>> {
+        feed.try_push(value).map_err(|err| match err {
+            | TrySendError::Full((tag, value)) => MuxError::Full(tag, value),
+            | TrySendError::Closed((tag, value)) => {
+                MuxError::Rejected(tag, value)
+            }
+        })
+    }
+
+    #[inline]
+    fn timed_out(err: SendTimeoutError<(T, V)>) -> MuxError<T, V> {
+        match err {
+            | SendTimeoutError::Timeout((tag, value)) => {
+                MuxError::Timeout(tag, value)
+            }
+            | SendTimeoutError::Closed((tag, value)) => {
+                MuxError::Rejected(tag, value)
+            }
+        }
+    }
+
+    /// Settles a single attempt at pushing a value to a lane.
+    ///
+    /// # Parameters
+    /// * `pushed` - Whether the value was pushed or dropped, or why it was not,
+    ///   where [`MuxError::Rejected`] stands for a closed lane.
+    /// * `lane_rx` - The receiver of the lane, if it was just created.
+    ///
+    /// # Returns
+    /// * [`ControlFlow::Break`] - The result of pushing the value.
+    /// * [`ControlFlow::Continue`] - The tag and value to retry with, as the
+    ///   existing lane was closed meanwhile.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn settle(
+        pushed: Result<bool, MuxError<T, V>>,
+        lane_rx: Option<LaneRx<T, V>>,
+    ) -> ControlFlow<Result<Delivery<LaneRx<T, V>>, MuxError<T, V>>, (T, V)>
+    {
+        match (pushed, lane_rx) {
+            | (Ok(true), Some(lane_rx)) => {
+                ControlFlow::Break(Ok(Delivery::New(lane_rx)))
+            }
+            | (Ok(true), None) => ControlFlow::Break(Ok(Delivery::Existing)),
+            | (Ok(false), _) => ControlFlow::Break(Ok(Delivery::Dropped)),
+            | (Err(MuxError::Rejected(tag, value)), None) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    ?tag,
+                    "lane was closed while pushing, retrying"
+                );
+
+                ControlFlow::Continue((tag, value))
+            }
+            | (Err(err), _) => ControlFlow::Break(Err(err)),
+        }
+    }
+
+    #[inline]
+    fn create(
+        &self,
+        slot: LaneTxSlot<T, V>,
+        lane_rx: &mut Option<LaneRx<T, V>>,
+    ) -> Inlet<T, V> {
+        let overflow = self
+            .overflows
+            .get(slot.key())
+            .map_or(self.overflow, |overflow| *overflow);
+        let idle = match self.idles.get(slot.key()) {
+            | Some(timeout) => Some(*timeout),
+            | None => *self.idle.lock().unwrap_or_else(|err| err.into_inner()),
+        };
+        let capacity = match overflow {
+            | Overflow::Unbounded => None,
+            | _ => Some(self.lane_buf),
+        };
+        let meter = Meter::new(self.totals.clone(), capacity, idle);
+        #[cfg(feature = "tracing")]
+        let meter = {
+            let span = tracing::debug_span!("lane", tag = ?slot.key());
+
+            tracing::debug!(
+                parent: &span,
+                ?overflow,
+                ?capacity,
+                ?idle,
+                "lane created"
+            );
+
+            meter.instrumented(span)
+        };
+        // the largest bound supported, which is never reached in practice
+        let (tx, rx) =
+            mpsc::channel(capacity.unwrap_or(Semaphore::MAX_PERMITS));
+        let lifecycle = Lifecycle::new(slot.key().clone(), self.events.clone());
+        let (rx, inlet) = LaneRx::new(
+            tx,
+            rx,
+            slot,
+            self.hooks.as_ref(),
+            self.track(),
+            overflow,
+            self.budget.clone(),
+            Arc::new(meter),
+            Arc::new(lifecycle),
+        );
+
+        *lane_rx = Some(rx);
+
+        inlet
+    }
 }
 
 impl<T: Key, V> Drop for Bus<T, V> {
@@ -0,0 +1,27 @@
+use std::io;
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use super::Codec;
+
+/// A pass-through [`Codec`] for raw byte payloads.
+///
+/// This codec is not self-delimiting: decoding consumes all of the given
+/// bytes, so it can only be used for values, not for tags.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BytesCodec;
+
+impl Codec for BytesCodec {
+    type Item = Bytes;
+
+    #[inline]
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> io::Result<()> {
+        dst.put(item);
+        Ok(())
+    }
+
+    #[inline]
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Bytes> {
+        Ok(src.split().freeze())
+    }
+}
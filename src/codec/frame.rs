@@ -0,0 +1,217 @@
+use std::io;
+
+use bytes::{Buf, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use super::varint::{peek_varint, put_varint};
+use super::{invalid_data, Codec};
+
+/// The default maximum length of a single frame (8 MiB).
+const DEFAULT_MAX_FRAME_LEN: usize = 8 * 1024 * 1024;
+
+/// A [`Decoder`] and [`Encoder`] of `(tag, value)` frames, that encodes tags
+/// with `TC` and values with `VC`.
+///
+/// Each frame is a varint length, followed by the encoded tag, and then the
+/// encoded value.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameCodec<TC, VC> {
+    tag: TC,
+    value: VC,
+    max_frame_len: usize,
+}
+
+impl<TC, VC> FrameCodec<TC, VC> {
+    /// Creates a new instance of [`FrameCodec`].
+    ///
+    /// # Parameters
+    /// * `tag` - The codec used to encode and decode tags.
+    /// * `value` - The codec used to encode and decode values.
+    #[inline]
+    pub const fn new(tag: TC, value: VC) -> Self {
+        Self {
+            tag,
+            value,
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+        }
+    }
+
+    /// Sets the maximum length of a single frame, defaults to 8 MiB.
+    ///
+    /// Encoding or decoding a larger frame fails with
+    /// [`io::ErrorKind::InvalidData`].
+    #[inline]
+    pub const fn with_max_frame_len(mut self, max_frame_len: usize) -> Self {
+        self.max_frame_len = max_frame_len;
+        self
+    }
+
+    /// Gets the maximum length of a single frame.
+    #[inline]
+    pub const fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+}
+
+impl<TC, VC> Default for FrameCodec<TC, VC>
+where
+    TC: Default,
+    VC: Default,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new(TC::default(), VC::default())
+    }
+}
+
+impl<TC, VC> Encoder<(TC::Item, VC::Item)> for FrameCodec<TC, VC>
+where
+    TC: Codec,
+    VC: Codec,
+{
+    type Error = io::Error;
+
+    fn encode(
+        &mut self,
+        (tag, value): (TC::Item, VC::Item),
+        dst: &mut BytesMut,
+    ) -> io::Result<()> {
+        self.encode_frame(dst, |tc, vc, body| {
+            tc.encode(tag, body)?;
+            vc.encode(value, body)
+        })
+    }
+}
+
+impl<TC, VC> FrameCodec<TC, VC> {
+    /// Gets mutable references to the tag and value codecs.
+    #[cfg(feature = "transport")]
+    #[inline]
+    pub(crate) fn codecs_mut(&mut self) -> (&mut TC, &mut VC) {
+        (&mut self.tag, &mut self.value)
+    }
+
+    /// Appends a frame to `dst`, whose body is written by `body`.
+    ///
+    /// Nothing is appended if `body` fails or the frame is too large.
+    pub(crate) fn encode_frame<F>(
+        &mut self,
+        dst: &mut BytesMut,
+        body: F,
+    ) -> io::Result<()>
+    where
+        F: FnOnce(&mut TC, &mut VC, &mut BytesMut) -> io::Result<()>,
+    {
+        let mut buf = dst.split_off(dst.len());
+
+        body(&mut self.tag, &mut self.value, &mut buf)?;
+
+        if buf.len() > self.max_frame_len {
+            return Err(invalid_data("frame too large"));
+        }
+
+        put_varint(dst, buf.len() as u64);
+        dst.unsplit(buf);
+
+        Ok(())
+    }
+
+    /// Splits the next complete frame body off `src`, if any.
+    pub(crate) fn decode_frame(
+        &self,
+        src: &mut BytesMut,
+    ) -> io::Result<Option<BytesMut>> {
+        let Some((len, len_len)) = peek_varint(src)? else {
+            return Ok(None);
+        };
+
+        let len = usize::try_from(len)
+            .ok()
+            .filter(|len| *len <= self.max_frame_len)
+            .ok_or_else(|| invalid_data("frame too large"))?;
+
+        if src.len() < len_len + len {
+            src.reserve(len_len + len - src.len());
+            return Ok(None);
+        }
+
+        src.advance(len_len);
+
+        Ok(Some(src.split_to(len)))
+    }
+}
+
+impl<TC, VC> Decoder for FrameCodec<TC, VC>
+where
+    TC: Codec,
+    VC: Codec,
+{
+    type Error = io::Error;
+    type Item = (TC::Item, VC::Item);
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Self::Item>> {
+        let Some(mut body) = self.decode_frame(src)? else {
+            return Ok(None);
+        };
+
+        let tag = self.tag.decode(&mut body)?;
+        let value = self.value.decode(&mut body)?;
+
+        if !body.is_empty() {
+            return Err(invalid_data("trailing bytes in frame"));
+        }
+
+        Ok(Some((tag, value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::codec::{BytesCodec, StrCodec, VarintCodec};
+
+    #[test]
+    fn frame_codec_test() {
+        let mut codec = FrameCodec::new(StrCodec, BytesCodec);
+        let mut buf = BytesMut::new();
+
+        let frames = [
+            ("orders.eu".to_owned(), Bytes::from_static(b"created")),
+            (String::new(), Bytes::new()),
+            ("x".repeat(300), Bytes::from(vec![0xAB; 1000])),
+        ];
+
+        for frame in frames.clone() {
+            codec.encode(frame, &mut buf).unwrap();
+        }
+
+        // feed the encoded bytes one at a time to exercise partial frames
+        let mut src = BytesMut::new();
+        let mut decoded = Vec::new();
+
+        for byte in buf {
+            src.extend_from_slice(&[byte]);
+
+            if let Some(frame) = codec.decode(&mut src).unwrap() {
+                decoded.push(frame);
+            }
+        }
+
+        assert_eq!(frames.to_vec(), decoded);
+        assert!(src.is_empty());
+
+        let mut codec = FrameCodec::new(VarintCodec::<u32>::new(), BytesCodec)
+            .with_max_frame_len(4);
+        let mut buf = BytesMut::new();
+
+        assert!(codec
+            .encode((1u32, Bytes::from_static(b"abcd")), &mut buf)
+            .is_err());
+        assert!(buf.is_empty());
+
+        buf.extend_from_slice(&[5, 1, b'a', b'b', b'c', b'd']);
+        assert!(Decoder::decode(&mut codec, &mut buf).is_err());
+    }
+}
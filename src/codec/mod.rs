@@ -0,0 +1,66 @@
+//! Wire encoding of tags and values.
+//!
+//! A [`Codec`] knows how to encode and decode a single item (a tag or a
+//! value), and [`FrameCodec`] combines a tag codec and a value codec into a
+//! [`tokio_util::codec`] encoder/decoder for `(tag, value)` frames.
+
+mod bytes;
+mod frame;
+#[cfg(feature = "serde")]
+mod serde;
+mod string;
+mod varint;
+
+use std::io;
+
+use ::bytes::BytesMut;
+
+pub use self::bytes::BytesCodec;
+pub use self::frame::FrameCodec;
+#[cfg(feature = "serde")]
+pub use self::serde::SerdeCodec;
+pub use self::string::StrCodec;
+pub use self::varint::VarintCodec;
+#[cfg(feature = "transport")]
+pub(crate) use self::varint::{get_varint, put_varint};
+
+/// A type that can encode and decode items of type [`Codec::Item`].
+///
+/// Tags and values are encoded separately, and each frame carries exactly one
+/// of each. A frame is always fully buffered before it gets decoded, so
+/// implementations never have to deal with partial input.
+pub trait Codec {
+    /// The type of items this codec encodes and decodes.
+    type Item;
+
+    /// Encodes `item` into `dst`.
+    ///
+    /// # Parameters
+    /// * `item` - The item to encode.
+    /// * `dst` - The buffer to append the encoded item to.
+    fn encode(
+        &mut self,
+        item: Self::Item,
+        dst: &mut BytesMut,
+    ) -> io::Result<()>;
+
+    /// Decodes an item from the beginning of `src`, advancing `src` past the
+    /// consumed bytes.
+    ///
+    /// When decoding a value, `src` holds exactly the bytes of that value;
+    /// when decoding a tag, it holds the tag followed by the value, so tag
+    /// codecs must be self-delimiting.
+    ///
+    /// # Parameters
+    /// * `src` - The buffer to decode the item from.
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Self::Item>;
+}
+
+/// Creates an [`io::Error`] of kind [`io::ErrorKind::InvalidData`].
+#[inline]
+pub(crate) fn invalid_data<E>(err: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
@@ -0,0 +1,108 @@
+use std::io;
+use std::marker::PhantomData;
+
+use bytes::{Buf, BufMut, BytesMut};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::varint::{get_varint, put_varint};
+use super::{invalid_data, Codec};
+
+/// A [`Codec`] for any [`serde`] (de)serializable type, encoded as a varint
+/// length followed by the item as JSON.
+///
+/// Not all JSON values are self-delimiting (e.g. `1` followed by `2` reads as
+/// `12`), hence the length, which lets this codec be used for tags as well as
+/// values.
+///
+/// This is only available when the `serde` feature is enabled.
+#[derive(Debug)]
+pub struct SerdeCodec<T>(PhantomData<fn(T) -> T>);
+
+impl<T> SerdeCodec<T> {
+    /// Creates a new instance of [`SerdeCodec`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Clone for SerdeCodec<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SerdeCodec<T> {}
+
+impl<T> Default for SerdeCodec<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Codec for SerdeCodec<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    type Item = T;
+
+    #[inline]
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> io::Result<()> {
+        let json = serde_json::to_vec(&item).map_err(invalid_data)?;
+
+        put_varint(dst, json.len() as u64);
+        dst.put_slice(&json);
+
+        Ok(())
+    }
+
+    #[inline]
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<T> {
+        let len = usize::try_from(get_varint(src)?).map_err(invalid_data)?;
+
+        if src.remaining() < len {
+            return Err(invalid_data("truncated value"));
+        }
+
+        serde_json::from_slice(&src.split_to(len)).map_err(invalid_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde_codec_test() {
+        let mut codec = SerdeCodec::<(String, Vec<u32>)>::new();
+        let mut buf = BytesMut::new();
+        let items = [("a".to_owned(), vec![1, 2]), ("b".to_owned(), vec![])];
+
+        for item in items.clone() {
+            codec.encode(item, &mut buf).unwrap();
+        }
+
+        for item in items {
+            assert_eq!(item, codec.decode(&mut buf).unwrap());
+        }
+
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn serde_codec_number_test() {
+        let mut codec = SerdeCodec::<u32>::new();
+        let mut buf = BytesMut::new();
+
+        // a numeric tag followed by a numeric value
+        codec.encode(1, &mut buf).unwrap();
+        codec.encode(2, &mut buf).unwrap();
+
+        assert_eq!(1, codec.decode(&mut buf).unwrap());
+        assert_eq!(2, codec.decode(&mut buf).unwrap());
+        assert!(buf.is_empty());
+    }
+}
@@ -0,0 +1,34 @@
+use std::io;
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use super::varint::{get_varint, put_varint};
+use super::{invalid_data, Codec};
+
+/// A [`Codec`] for strings, encoded as a varint length followed by the UTF-8
+/// bytes of the string.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StrCodec;
+
+impl Codec for StrCodec {
+    type Item = String;
+
+    #[inline]
+    fn encode(&mut self, item: String, dst: &mut BytesMut) -> io::Result<()> {
+        put_varint(dst, item.len() as u64);
+        dst.put_slice(item.as_bytes());
+
+        Ok(())
+    }
+
+    #[inline]
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<String> {
+        let len = usize::try_from(get_varint(src)?).map_err(invalid_data)?;
+
+        if src.remaining() < len {
+            return Err(invalid_data("truncated string"));
+        }
+
+        String::from_utf8(src.split_to(len).to_vec()).map_err(invalid_data)
+    }
+}
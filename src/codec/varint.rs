@@ -0,0 +1,178 @@
+use std::io;
+use std::marker::PhantomData;
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use super::{invalid_data, Codec};
+
+/// The maximum number of bytes a LEB128-encoded [`u64`] can take.
+const MAX_LEN: usize = 10;
+
+/// A [`Codec`] for integers of type `T`, encoded as LEB128 variable length
+/// integers.
+///
+/// Signed integers are zig-zag encoded first, so that small negative numbers
+/// stay small on the wire.
+#[derive(Debug)]
+pub struct VarintCodec<T = u64>(PhantomData<fn(T) -> T>);
+
+impl<T> VarintCodec<T> {
+    /// Creates a new instance of [`VarintCodec`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Clone for VarintCodec<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for VarintCodec<T> {}
+
+impl<T> Default for VarintCodec<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Appends `value` to `dst` as a LEB128 variable length integer.
+#[inline]
+pub(crate) fn put_varint(dst: &mut BytesMut, mut value: u64) {
+    while value >= 0x80 {
+        dst.put_u8(value as u8 | 0x80);
+        value >>= 7;
+    }
+
+    dst.put_u8(value as u8);
+}
+
+/// Reads a LEB128 variable length integer from the beginning of `src`
+/// without consuming it.
+///
+/// # Returns
+/// * [`Ok(Some((value, len)))`] - The decoded value and its encoded length.
+/// * [`Ok(None)`] - If `src` does not hold a complete integer yet.
+/// * [`Err(io::Error)`] - If the integer overflows a [`u64`].
+#[inline]
+pub(crate) fn peek_varint(src: &[u8]) -> io::Result<Option<(u64, usize)>> {
+    let mut value = 0u64;
+
+    for (i, byte) in src.iter().take(MAX_LEN).enumerate() {
+        let bits = u64::from(byte & 0x7F);
+
+        if i == MAX_LEN - 1 && bits > 1 {
+            return Err(invalid_data("varint overflows 64 bits"));
+        }
+
+        value |= bits << (7 * i);
+
+        if byte & 0x80 == 0 {
+            return Ok(Some((value, i + 1)));
+        }
+    }
+
+    if src.len() >= MAX_LEN {
+        return Err(invalid_data("varint overflows 64 bits"));
+    }
+
+    Ok(None)
+}
+
+/// Reads a LEB128 variable length integer from the beginning of `src`,
+/// advancing `src` past it.
+#[inline]
+pub(crate) fn get_varint(src: &mut BytesMut) -> io::Result<u64> {
+    let (value, len) =
+        peek_varint(src)?.ok_or_else(|| invalid_data("truncated varint"))?;
+
+    src.advance(len);
+
+    Ok(value)
+}
+
+macro_rules! impl_unsigned {
+    ($($ty:ty),*) => {$(
+        impl Codec for VarintCodec<$ty> {
+            type Item = $ty;
+
+            #[inline]
+            fn encode(&mut self, item: $ty, dst: &mut BytesMut) -> io::Result<()> {
+                put_varint(dst, item as u64);
+                Ok(())
+            }
+
+            #[inline]
+            fn decode(&mut self, src: &mut BytesMut) -> io::Result<$ty> {
+                <$ty>::try_from(get_varint(src)?).map_err(invalid_data)
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_signed {
+    ($($ty:ty),*) => {$(
+        impl Codec for VarintCodec<$ty> {
+            type Item = $ty;
+
+            #[inline]
+            fn encode(&mut self, item: $ty, dst: &mut BytesMut) -> io::Result<()> {
+                let item = item as i64;
+
+                put_varint(dst, ((item << 1) ^ (item >> 63)) as u64);
+                Ok(())
+            }
+
+            #[inline]
+            fn decode(&mut self, src: &mut BytesMut) -> io::Result<$ty> {
+                let value = get_varint(src)?;
+                let value = ((value >> 1) as i64) ^ -((value & 1) as i64);
+
+                <$ty>::try_from(value).map_err(invalid_data)
+            }
+        }
+    )*};
+}
+
+impl_unsigned!(u8, u16, u32, u64, usize);
+impl_signed!(i8, i16, i32, i64, isize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn varint_test() {
+        fn roundtrip<T>(item: T, len: usize)
+        where
+            T: Copy + std::fmt::Debug + PartialEq,
+            VarintCodec<T>: Codec<Item = T>,
+        {
+            let mut codec = VarintCodec::<T>::new();
+            let mut buf = BytesMut::new();
+
+            codec.encode(item, &mut buf).unwrap();
+            assert_eq!(len, buf.len(), "{item:?}");
+            assert_eq!(item, codec.decode(&mut buf).unwrap());
+            assert!(buf.is_empty());
+        }
+
+        roundtrip(0u8, 1);
+        roundtrip(0x7Fu16, 1);
+        roundtrip(0x80u32, 2);
+        roundtrip(u64::MAX, MAX_LEN);
+        roundtrip(-1i32, 1);
+        roundtrip(i64::MIN, MAX_LEN);
+
+        let mut buf = BytesMut::new();
+
+        VarintCodec::<u32>::new().encode(0x1FF, &mut buf).unwrap();
+        assert!(VarintCodec::<u8>::new().decode(&mut buf).is_err());
+        assert!(peek_varint(&[0x80, 0x80]).unwrap().is_none());
+        assert!(peek_varint(&[0xFF; MAX_LEN]).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::CloseReason;
+
+/// The default number of lane events buffered for each observer, past which
+/// slow observers miss the oldest ones.
+pub const DEFAULT_EVENTS_BUF: usize = 256;
+
+/// A sender of [`LaneEvent`]s to all observers of a [`Bus`](crate::bus::Bus).
+pub(crate) type Events<T> = broadcast::Sender<LaneEvent<T>>;
+
+/// A lifecycle event of a lane.
+///
+/// Each lane emits [`LaneEvent::Opened`] first, and either
+/// [`LaneEvent::Closed`] or [`LaneEvent::Evicted`] once it stops receiving
+/// values.
+///
+/// See [`Mux::events`](crate::mux::Mux::events).
+#[derive(Debug, Clone)]
+pub enum LaneEvent<T> {
+    /// A new lane was opened, be it by a value sent to a new tag, or
+    /// explicitly.
+    Opened(T),
+    /// All senders of the lane were dropped, so no more values are sent
+    /// through it.
+    HalfClosed(T),
+    /// The lane was closed for the given reason, so no more values are
+    /// received from it, apart from those already buffered in it.
+    Closed {
+        /// The tag of the lane.
+        tag: T,
+        /// Why the lane was closed.
+        reason: CloseReason,
+    },
+    /// The lane was the least recently used one, and was evicted to make room
+    /// for a new lane (see
+    /// [`AtCapacity::EvictLru`](crate::admission::AtCapacity::EvictLru)).
+    Evicted(T),
+    /// A value was pushed to the lane while it was full, so its
+    /// [`Overflow`](crate::overflow::Overflow) policy kicked in.
+    Overflowed(T),
+}
+
+impl<T> LaneEvent<T> {
+    /// Gets the tag of the lane the event is about.
+    #[inline]
+    pub const fn tag(&self) -> &T {
+        match self {
+            | LaneEvent::Opened(tag)
+            | LaneEvent::HalfClosed(tag)
+            | LaneEvent::Closed { tag, .. }
+            | LaneEvent::Evicted(tag)
+            | LaneEvent::Overflowed(tag) => tag,
+        }
+    }
+}
+
+/// A receiver of the lifecycle events of the lanes of a
+/// [`Mux`](crate::mux::Mux).
+///
+/// Each receiver sees every event emitted since it was created, unless it
+/// falls behind by more than its buffer size, in which case the oldest events
+/// are skipped (see [`LaneEvents::missed`]).
+///
+/// See [`Mux::events`](crate::mux::Mux::events).
+#[derive(Debug)]
+pub struct LaneEvents<T> {
+    inner: broadcast::Receiver<LaneEvent<T>>,
+    missed: u64,
+}
+
+impl<T: Clone> LaneEvents<T> {
+    /// Create a new receiver of lane events.
+    ///
+    /// # Parameters
+    /// * `inner` - The underlying channel receiver.
+    #[inline]
+    pub(crate) const fn new(inner: broadcast::Receiver<LaneEvent<T>>) -> Self {
+        Self { inner, missed: 0 }
+    }
+
+    /// Receives the next lane event.
+    ///
+    /// # Returns
+    /// * [`Some(event)`] - The next lane event.
+    /// * [`None`] - If the mux and all of its lanes were dropped.
+    pub async fn recv(&mut self) -> Option<LaneEvent<T>> {
+        loop {
+            match self.inner.recv().await {
+                | Ok(event) => return Some(event),
+                | Err(RecvError::Lagged(missed)) => self.missed += missed,
+                | Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Gets the number of events skipped so far, as this receiver fell behind.
+    #[inline]
+    pub const fn missed(&self) -> u64 {
+        self.missed
+    }
+}
@@ -0,0 +1,160 @@
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+use dashmap::DashMap;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{Key, LaneRx};
+
+/// The default number of values buffered for each subscriber of a fan-out
+/// lane, past which slow subscribers miss the oldest ones.
+pub const DEFAULT_FANOUT_BUF: usize = 64;
+
+/// The fan-out lanes of a [`Mux`](crate::mux::Mux), by tag.
+pub(crate) type Fanouts<T, V> = Arc<DashMap<T, Weak<Fanout<T, V>>>>;
+
+/// A lane whose values are received by all of its subscribers.
+///
+/// Values are pulled from the lane as subscribers ask for them, so the lane
+/// drains as fast as its fastest subscriber.
+#[derive(Debug)]
+pub(crate) struct Fanout<T: Key, V> {
+    tag: T,
+    // held by the subscriber pulling values from the lane
+    rx: tokio::sync::Mutex<LaneRx<T, V>>,
+    // dropped once the lane is closed, which ends the subscribers
+    tx: Mutex<Option<broadcast::Sender<V>>>,
+    fanouts: Fanouts<T, V>,
+}
+
+impl<T: Key, V: Clone> Fanout<T, V> {
+    /// Creates a new instance of [`Fanout`].
+    ///
+    /// # Parameters
+    /// * `rx` - The receiver of the lane.
+    /// * `buf` - The number of values buffered for each subscriber.
+    /// * `fanouts` - The fan-out lanes the lane is registered in.
+    #[inline]
+    pub(crate) fn new(
+        rx: LaneRx<T, V>,
+        buf: usize,
+        fanouts: Fanouts<T, V>,
+    ) -> Self {
+        Self {
+            tag: rx.tag().clone(),
+            rx: tokio::sync::Mutex::new(rx),
+            tx: Mutex::new(Some(broadcast::Sender::new(buf.max(1)))),
+            fanouts,
+        }
+    }
+
+    /// Adds a new subscriber, which receives the values pulled from the lane
+    /// from now on.
+    ///
+    /// # Returns
+    /// * [`Some(rx)`] - The new subscriber.
+    /// * [`None`] - If the lane was closed.
+    pub(crate) fn subscribe(self: &Arc<Self>) -> Option<FanoutRx<T, V>> {
+        let inner = lock(&self.tx).as_ref()?.subscribe();
+
+        Some(FanoutRx {
+            inner,
+            fanout: self.clone(),
+            missed: 0,
+        })
+    }
+
+    /// Hands a value pulled from the lane to all subscribers, or ends them if
+    /// the lane was closed.
+    #[inline]
+    fn publish(&self, value: Option<V>) {
+        let mut tx = lock(&self.tx);
+
+        match (value, tx.as_ref()) {
+            | (Some(value), Some(tx)) => _ = tx.send(value),
+            | (Some(_), None) => {}
+            | (None, _) => _ = tx.take(),
+        }
+    }
+}
+
+impl<T: Key, V> Drop for Fanout<T, V> {
+    #[inline]
+    fn drop(&mut self) {
+        self.fanouts
+            .remove_if(&self.tag, |_, fanout| fanout.strong_count() == 0);
+    }
+}
+
+/// A subscriber of a fan-out lane, which receives every value sent to the
+/// lane, unless it falls behind by more than its buffer size, in which case
+/// the oldest values are skipped (see [`FanoutRx::missed`]).
+///
+/// The lane is closed once all of its subscribers are dropped.
+///
+/// See [`Mux::subscribe`](crate::mux::Mux::subscribe).
+#[derive(Debug)]
+pub struct FanoutRx<T: Key, V> {
+    inner: broadcast::Receiver<V>,
+    fanout: Arc<Fanout<T, V>>,
+    missed: u64,
+}
+
+impl<T: Key, V: Clone> FanoutRx<T, V> {
+    /// Gets the tag of the lane.
+    #[inline]
+    pub fn tag(&self) -> &T {
+        &self.fanout.tag
+    }
+
+    /// Receives the next value sent to the lane.
+    ///
+    /// # Returns
+    /// * [`Some(value)`] - The received value.
+    /// * [`None`] - If the lane was closed, and all values already pulled from
+    ///   it were received.
+    pub async fn recv(&mut self) -> Option<V> {
+        loop {
+            let received = tokio::select! {
+                biased;
+
+                received = self.inner.recv() => received,
+                mut rx = self.fanout.rx.lock() => {
+                    // pull the next value for all subscribers, unless another
+                    // subscriber does so first
+                    tokio::select! {
+                        biased;
+
+                        received = self.inner.recv() => received,
+                        value = rx.recv() => {
+                            self.fanout.publish(value);
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            match received {
+                | Ok(value) => return Some(value),
+                | Err(RecvError::Lagged(missed)) => self.missed += missed,
+                | Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Gets the number of values skipped so far, as this subscriber fell
+    /// behind.
+    #[inline]
+    pub const fn missed(&self) -> u64 {
+        self.missed
+    }
+}
+
+/// Locks the sender of a fan-out lane, ignoring poisoning as it is never left
+/// in an inconsistent state.
+#[inline]
+fn lock<V>(
+    tx: &Mutex<Option<broadcast::Sender<V>>>,
+) -> MutexGuard<'_, Option<broadcast::Sender<V>>> {
+    tx.lock().unwrap_or_else(|err| err.into_inner())
+}
@@ -0,0 +1,362 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tokio::sync::Notify;
+
+use crate::{Key, LaneRx};
+
+/// The default number of values that can be assigned to a worker of a
+/// [`ConsumerGroup`] before it receives them.
+pub const DEFAULT_PREFETCH: usize = 1;
+
+/// How the values of a [`ConsumerGroup`]'s lane are assigned to its workers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Assignment {
+    /// Values are assigned to the workers in turn, skipping workers that have
+    /// a full prefetch of values waiting for them.
+    #[default]
+    RoundRobin,
+    /// Values are assigned to the worker with the fewest values waiting for
+    /// it, counting the one it is processing, so that idle workers take values
+    /// as they ask for them.
+    LeastLoaded,
+}
+
+/// A group of workers competing for the values of a single lane, where each
+/// value is received by exactly one of the workers.
+///
+/// Workers join the group with [`ConsumerGroup::join`]. Values are pulled from
+/// the lane as workers ask for them, and assigned to the workers as per the
+/// group's [`Assignment`]. Once a worker is dropped, the values that were
+/// assigned to it but not received yet are handed to the remaining workers.
+///
+/// The lane is closed once the group and all of its workers are dropped.
+#[derive(Debug)]
+pub struct ConsumerGroup<T: Key, V>(Arc<Shared<T, V>>);
+
+/// A worker of a [`ConsumerGroup`].
+#[derive(Debug)]
+pub struct Worker<T: Key, V> {
+    id: u64,
+    notify: Arc<Notify>,
+    group: Arc<Shared<T, V>>,
+}
+
+/// The state shared by a group and its workers.
+#[derive(Debug)]
+struct Shared<T: Key, V> {
+    tag: T,
+    // held by the worker pulling values from the lane
+    rx: tokio::sync::Mutex<LaneRx<T, V>>,
+    state: Mutex<State<V>>,
+}
+
+#[derive(Debug)]
+struct State<V> {
+    assignment: Assignment,
+    prefetch: usize,
+    workers: Vec<Slot<V>>,
+    cursor: usize,
+    next_id: u64,
+    // values left behind by dropped workers, taken by whoever asks first
+    backlog: VecDeque<V>,
+    closed: bool,
+}
+
+/// A worker's share of a group's values.
+#[derive(Debug)]
+struct Slot<V> {
+    id: u64,
+    queue: VecDeque<V>,
+    busy: bool,
+    notify: Arc<Notify>,
+}
+
+impl<T: Key, V> Clone for ConsumerGroup<T, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Key, V> ConsumerGroup<T, V> {
+    /// Create a new instance of [`ConsumerGroup`], with no workers yet.
+    ///
+    /// # Parameters
+    /// * `rx` - The receiver of the lane to share among the workers.
+    /// * `assignment` - How values are assigned to the workers.
+    #[inline]
+    pub fn new(rx: LaneRx<T, V>, assignment: Assignment) -> Self {
+        let state = State {
+            assignment,
+            prefetch: DEFAULT_PREFETCH,
+            workers: Vec::new(),
+            cursor: 0,
+            next_id: 0,
+            backlog: VecDeque::new(),
+            closed: false,
+        };
+
+        Self(Arc::new(Shared {
+            tag: rx.tag().clone(),
+            rx: tokio::sync::Mutex::new(rx),
+            state: Mutex::new(state),
+        }))
+    }
+
+    /// Sets how many values can be assigned to a worker before it receives
+    /// them.
+    ///
+    /// # Parameters
+    /// * `prefetch` - The number of values, [`DEFAULT_PREFETCH`] by default.
+    #[inline]
+    pub fn with_prefetch(self, prefetch: usize) -> Self {
+        lock(&self.0.state).prefetch = prefetch.max(1);
+        self
+    }
+
+    /// Gets the tag of the group's lane.
+    #[inline]
+    pub fn tag(&self) -> &T {
+        &self.0.tag
+    }
+
+    /// Gets the number of workers in the group.
+    #[inline]
+    pub fn workers(&self) -> usize {
+        lock(&self.0.state).workers.len()
+    }
+
+    /// Adds a new worker to the group.
+    pub fn join(&self) -> Worker<T, V> {
+        let mut state = lock(&self.0.state);
+        let id = state.next_id;
+        let notify = Arc::new(Notify::new());
+
+        state.next_id += 1;
+        state.workers.push(Slot {
+            id,
+            queue: VecDeque::new(),
+            busy: false,
+            notify: notify.clone(),
+        });
+
+        Worker {
+            id,
+            notify,
+            group: self.0.clone(),
+        }
+    }
+}
+
+impl<T: Key, V> Worker<T, V> {
+    /// Gets the tag of the group's lane.
+    #[inline]
+    pub fn tag(&self) -> &T {
+        &self.group.tag
+    }
+
+    /// Receives the next value assigned to this worker.
+    ///
+    /// Asking for a value marks the previous one as processed, as far as
+    /// [`Assignment::LeastLoaded`] is concerned.
+    ///
+    /// # Returns
+    /// * [`Some(value)`] - The received value.
+    /// * [`None`] - If the lane was closed, and all of its values were
+    ///   received.
+    pub async fn recv(&mut self) -> Option<V> {
+        loop {
+            {
+                let mut state = lock(&self.group.state);
+
+                if let Some(value) = state.take(self.id) {
+                    return Some(value);
+                }
+
+                if state.closed {
+                    return None;
+                }
+            }
+
+            tokio::select! {
+                () = self.notify.notified() => {}
+                mut rx = self.group.rx.lock() => {
+                    // another worker might have left values behind meanwhile
+                    if lock(&self.group.state).has_pending(self.id) {
+                        continue;
+                    }
+
+                    tokio::select! {
+                        value = rx.recv() => {
+                            lock(&self.group.state).assign(value, self.id);
+                        }
+                        () = self.notify.notified() => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T: Key, V> Drop for Worker<T, V> {
+    fn drop(&mut self) {
+        let mut state = lock(&self.group.state);
+        let Some(idx) = state.position(self.id) else {
+            return;
+        };
+        let slot = state.workers.remove(idx);
+
+        if state.cursor > idx {
+            state.cursor -= 1;
+        }
+
+        // the values assigned to this worker are handed to the others
+        for value in slot.queue.into_iter().rev() {
+            state.backlog.push_front(value);
+        }
+
+        if !state.backlog.is_empty() {
+            state.notify_all();
+        }
+    }
+}
+
+impl<V> State<V> {
+    /// Takes the next value for the worker with the given id, marking it as
+    /// busy processing it.
+    fn take(&mut self, id: u64) -> Option<V> {
+        let idx = self.position(id)?;
+        let value = match self.workers[idx].queue.pop_front() {
+            | Some(value) => Some(value),
+            | None => self.backlog.pop_front(),
+        };
+
+        self.workers[idx].busy = value.is_some();
+
+        value
+    }
+
+    /// Gets whether any value is waiting for the worker with the given id.
+    #[inline]
+    fn has_pending(&self, id: u64) -> bool {
+        !self.backlog.is_empty()
+            || self
+                .position(id)
+                .is_some_and(|idx| !self.workers[idx].queue.is_empty())
+    }
+
+    /// Assigns a value pulled from the lane by the worker with the given id,
+    /// or marks the lane as closed.
+    fn assign(&mut self, value: Option<V>, puller: u64) {
+        let Some(value) = value else {
+            self.closed = true;
+            return self.notify_all();
+        };
+
+        let prefetch = self.prefetch;
+        // the puller has nothing waiting for it, so it can always take it
+        let eligible =
+            |slot: &Slot<V>| slot.id == puller || slot.queue.len() < prefetch;
+        let count = self.workers.len();
+        let idx = match self.assignment {
+            | Assignment::RoundRobin => (0..count)
+                .map(|n| (self.cursor + n) % count)
+                .find(|&idx| eligible(&self.workers[idx])),
+            | Assignment::LeastLoaded => self
+                .workers
+                .iter()
+                .enumerate()
+                .filter(|(_, slot)| eligible(slot))
+                .min_by_key(|(_, slot)| {
+                    let load = slot.queue.len() + usize::from(slot.busy);
+
+                    (load, slot.id != puller)
+                })
+                .map(|(idx, _)| idx),
+        };
+        let Some(idx) = idx else {
+            return self.backlog.push_back(value);
+        };
+        let slot = &mut self.workers[idx];
+
+        slot.queue.push_back(value);
+
+        if slot.id != puller {
+            slot.notify.notify_one();
+        }
+
+        self.cursor = (idx + 1) % count;
+    }
+
+    #[inline]
+    fn position(&self, id: u64) -> Option<usize> {
+        self.workers.iter().position(|slot| slot.id == id)
+    }
+
+    #[inline]
+    fn notify_all(&self) {
+        for slot in &self.workers {
+            slot.notify.notify_one();
+        }
+    }
+}
+
+/// Locks the state of a group, ignoring poisoning as the state is never left
+/// inconsistent.
+#[inline]
+fn lock<V>(state: &Mutex<State<V>>) -> MutexGuard<'_, State<V>> {
+    state.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Mux;
+
+    #[tokio::test]
+    async fn group_test() {
+        let (mut mux, _mux_rx) = Mux::new(8, 8);
+        let group = mux.group(1, Assignment::RoundRobin).unwrap();
+        let mut a = group.join();
+        let mut b = group.join();
+
+        for value in 0..6 {
+            mux.send(1, value).await.unwrap();
+        }
+
+        // values are handed out in turn, whoever pulls them from the lane
+        assert_eq!(Some(0), a.recv().await);
+        assert_eq!(Some(2), a.recv().await);
+        assert_eq!(Some(1), b.recv().await);
+
+        // values left behind by a dropped worker go to the others
+        assert_eq!(Some(4), a.recv().await);
+        drop(b);
+
+        assert_eq!(1, group.workers());
+        assert_eq!(Some(3), a.recv().await);
+        assert_eq!(Some(5), a.recv().await);
+
+        // idle workers take values first
+        let (mut other, _other_rx) = Mux::new(8, 8);
+        let least = other.group(2, Assignment::LeastLoaded).unwrap();
+        let mut busy = least.join();
+        let mut idle = least.join();
+
+        for value in 0..3 {
+            other.send(2, value).await.unwrap();
+        }
+
+        assert_eq!(Some(0), busy.recv().await);
+        assert_eq!(Some(1), idle.recv().await);
+        assert_eq!(Some(2), busy.recv().await);
+
+        // workers end once the lane is closed and drained
+        drop((mux, other, group, least));
+
+        assert_eq!(None, idle.recv().await);
+        assert_eq!(None, busy.recv().await);
+        assert_eq!(None, a.recv().await);
+    }
+}
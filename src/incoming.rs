@@ -0,0 +1,53 @@
+use std::task::{Context, Poll};
+
+use tokio::sync::mpsc;
+
+use crate::{Key, Lane};
+
+/// A receiver of lanes as they are created by a [`Mux`](crate::mux::Mux),
+/// much like a listener accepting connections.
+///
+/// See [`Mux::incoming`](crate::mux::Mux::incoming).
+#[derive(Debug)]
+pub struct Incoming<T: Key, V>(mpsc::Receiver<Lane<T, V>>);
+
+impl<T: Key, V> Incoming<T, V> {
+    /// Create a new receiver of lanes.
+    ///
+    /// # Parameters
+    /// * `inner` - The underlying channel receiver.
+    #[inline]
+    pub(crate) const fn new(inner: mpsc::Receiver<Lane<T, V>>) -> Self {
+        Self(inner)
+    }
+
+    /// Accepts the next new lane.
+    ///
+    /// # Returns
+    /// * [`Some(lane)`] - The new lane.
+    /// * [`None`] - If the mux was closed, or stopped delivering new lanes
+    ///   here.
+    #[inline]
+    pub async fn accept(&mut self) -> Option<Lane<T, V>> {
+        self.0.recv().await
+    }
+
+    /// Polls to accept the next new lane.
+    #[inline]
+    pub fn poll_accept(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Lane<T, V>>> {
+        self.0.poll_recv(cx)
+    }
+
+    /// Stops accepting new lanes.
+    ///
+    /// Lanes that were already delivered can still be accepted, while any
+    /// further lanes are returned from [`Mux::send`](crate::mux::Mux::send)
+    /// instead.
+    #[inline]
+    pub fn close(&mut self) {
+        self.0.close();
+    }
+}
@@ -1,17 +1,932 @@
-use std::task::{Context, Poll};
+use std::collections::VecDeque;
+use std::future::{poll_fn, Future};
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, Weak};
+use std::task::{ready, Context, Poll, Waker};
+use std::time::Duration;
 
-use tokio::sync::mpsc::error::SendError;
-use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::sync::mpsc::error::{SendError, SendTimeoutError, TrySendError};
+use tokio::sync::mpsc::{Receiver, Sender, UnboundedSender, WeakSender};
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore, TryAcquireError};
+use tokio::time::{Instant, Sleep};
 
-use crate::map::{Key, MapSlot};
+use crate::event::{Events, LaneEvent};
+use crate::map::{Key, Map, MapSlot};
+use crate::overflow::ParkBudget;
+use crate::stats::{LaneStats, Meter};
+use crate::Overflow;
 
-pub(crate) type LaneTxSlot<T, V> = MapSlot<T, LaneTx<T, V>>;
+pub(crate) type LaneTxSlot<T, V> = MapSlot<T, Inlet<T, V>>;
+
+/// The senders of a lane, once it has any, shared by its receiver and inlet
+/// so that more senders can be made for it from its tag.
+pub(crate) type Senders<T, V> = Arc<OnceLock<WeakLaneTx<T, V>>>;
+
+/// The buffer of a lane's inbound channel, shared with its [`Feed`] so that
+/// values can be evicted with [`Overflow::DropOldest`], or parked with
+/// [`Overflow::Park`].
+type Queue<V> = Mutex<Buffer<V>>;
+
+/// A value pushed to a lane, along with when it was pushed.
+#[derive(Debug)]
+pub(crate) struct Entry<V> {
+    value: V,
+    at: Instant,
+}
+
+impl<V> Entry<V> {
+    #[inline]
+    fn new(value: V) -> Self {
+        Self {
+            value,
+            at: Instant::now(),
+        }
+    }
+}
+
+/// A sender of lane lifecycle [`Signal`]s.
+pub(crate) type Signals<T> = UnboundedSender<Signal<T>>;
+
+/// A lifecycle notification emitted by the halves of a lane, used by
+/// transports to reflect local lane state on the remote end.
+#[derive(Debug)]
+// lane ids are only told apart by transports
+#[cfg_attr(not(feature = "transport"), allow(dead_code))]
+pub(crate) enum Signal<T> {
+    /// All [`LaneTx`] handles of the lane with the given id were dropped.
+    ///
+    /// The id tells lanes apart that reuse the same tag, so that the senders
+    /// of a lane that was replaced cannot half-close the lane that replaced
+    /// it (see [`Hooks::is_stale`]).
+    TxClosed(T, u64),
+    /// The [`LaneRx`] of the lane was closed or dropped.
+    RxClosed(T),
+    /// The [`LaneRx`] of the lane consumed values worth the given credit since
+    /// the last time this signal was emitted.
+    #[cfg(feature = "transport")]
+    Consumed(T, u32),
+}
+
+/// Gets the amount of credit a value takes from a lane's window.
+pub(crate) type Cost<V> = fn(&V) -> u32;
+
+/// Splits off the part of a value that costs the given amount of credit,
+/// leaving the rest of it in place.
+pub(crate) type Split<V> = fn(&mut V, u32) -> V;
+
+/// Hooks that let a transport observe and control the lanes of a mux.
+#[derive(Debug)]
+pub(crate) struct Hooks<T: Key, V> {
+    signals: Signals<T>,
+    credits: Map<T, Credit>,
+    next_id: Arc<AtomicU64>,
+    window: u32,
+    cost: Cost<V>,
+    split: Option<Split<V>>,
+}
+
+/// The send credit of a single lane, along with the id that tells it apart
+/// from other lanes with the same tag.
+#[derive(Debug, Clone)]
+struct Credit {
+    id: u64,
+    permits: Arc<Semaphore>,
+}
+
+impl<T: Key, V> Hooks<T, V> {
+    /// Creates a new instance of [`Hooks`], where each value costs a single
+    /// unit of credit.
+    ///
+    /// # Parameters
+    /// * `signals` - The sender to emit lifecycle signals through.
+    /// * `window` - The credit a lane can spend before it has to wait for more,
+    ///   which is also the credit a lane can consume before it grants more
+    ///   credit to the remote end.
+    #[cfg(feature = "transport")]
+    #[inline]
+    pub(crate) fn new(signals: Signals<T>, window: u32) -> Self {
+        Self {
+            signals,
+            credits: Map::new(),
+            next_id: Default::default(),
+            window: window.max(1),
+            cost: |_| 1,
+            split: None,
+        }
+    }
+
+    /// Sets the function used to get the amount of credit a value costs.
+    ///
+    /// Values that cost more than the whole window can never be sent, so
+    /// sending them fails right away as if the lane was closed, unless they
+    /// can be split (see [`Hooks::with_split`]).
+    ///
+    /// # Parameters
+    /// * `cost` - The cost function.
+    #[cfg(feature = "yamux")]
+    #[inline]
+    pub(crate) fn with_cost(mut self, cost: Cost<V>) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    /// Sets the function used to split values that cost more than the credit
+    /// at hand, so that as much of them is sent as the credit allows, and the
+    /// rest once more credit is granted.
+    ///
+    /// # Parameters
+    /// * `split` - The split function.
+    #[cfg(feature = "yamux")]
+    #[inline]
+    pub(crate) fn with_split(mut self, split: Split<V>) -> Self {
+        self.split = Some(split);
+        self
+    }
+
+    /// Gets the credit a lane can spend before it has to wait for more.
+    #[cfg(feature = "transport")]
+    #[inline]
+    pub(crate) const fn window(&self) -> u32 {
+        self.window
+    }
+
+    /// Grants more send credit to the lane with the given tag.
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    /// * `credit` - The credit the lane can additionally spend.
+    #[cfg(feature = "transport")]
+    #[inline]
+    pub(crate) fn grant(&self, tag: &T, credit: u32) {
+        if let Some(credits) = self.credits.get_mut(tag) {
+            credits.permits.add_permits(credit as usize);
+        }
+    }
+
+    /// Revokes all send credit of the lane with the given tag, making any
+    /// further sends fail.
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    #[cfg(feature = "transport")]
+    #[inline]
+    pub(crate) fn revoke(&self, tag: &T) {
+        if let Some(credits) = self.credits.get_mut(tag) {
+            credits.permits.close();
+        }
+    }
+
+    /// Gets whether `signal` was emitted by a lane that was since replaced by
+    /// another lane with the same tag, in which case it must be ignored.
+    ///
+    /// # Parameters
+    /// * `signal` - The signal to check.
+    #[cfg(feature = "transport")]
+    #[inline]
+    pub(crate) fn is_stale(&self, signal: &Signal<T>) -> bool {
+        match *signal {
+            | Signal::TxClosed(ref tag, id) => self
+                .credits
+                .get_mut(tag)
+                .is_some_and(|credits| credits.id != id),
+            | _ => false,
+        }
+    }
+
+    /// Emits a lifecycle signal on behalf of a lane.
+    ///
+    /// # Parameters
+    /// * `signal` - The signal to emit.
+    #[inline]
+    pub(crate) fn notify(&self, signal: Signal<T>) {
+        _ = self.signals.send(signal);
+    }
+
+    /// Gets the amount of credit `value` costs.
+    #[cfg(feature = "transport")]
+    #[inline]
+    pub(crate) fn cost(&self, value: &V) -> u32 {
+        (self.cost)(value)
+    }
+}
+
+impl<T: Key, V> Clone for Hooks<T, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            signals: self.signals.clone(),
+            credits: self.credits.clone(),
+            next_id: self.next_id.clone(),
+            window: self.window,
+            cost: self.cost,
+            split: self.split,
+        }
+    }
+}
+
+/// The reason a lane was closed, as returned by [`LaneRx::recv_result`].
+#[derive(Debug, Clone)]
+pub enum CloseReason {
+    /// The lane was closed by its sending end, e.g. the remote end
+    /// half-closed it.
+    Graceful,
+    /// The lane was closed by its receiver with [`LaneRx::close`].
+    LocalClose,
+    /// The mux the lane belongs to was shut down, aborted or dropped.
+    Shutdown,
+    /// The remote end reset the lane with the given error code.
+    Reset(u32),
+    /// The transport the lane ran over failed or was closed.
+    Transport(Arc<io::Error>),
+    /// The lane saw no traffic in either direction for its idle timeout (see
+    /// [`Mux::with_idle_timeout`](crate::mux::Mux::with_idle_timeout)).
+    Idle,
+    /// The lane was the least recently used one, and was evicted to make room
+    /// for a new lane (see
+    /// [`AtCapacity::EvictLru`](crate::admission::AtCapacity::EvictLru)).
+    Evicted,
+}
+
+/// When a lane last saw traffic in either direction, so that it can be
+/// evicted once it has been idle for too long, or once it is the least
+/// recently used one.
+#[derive(Debug)]
+pub(crate) struct Activity {
+    timeout: Option<Duration>,
+    since: Instant,
+    last: AtomicU64,
+}
+
+impl Activity {
+    /// Creates a new instance of [`Activity`], as of now.
+    ///
+    /// # Parameters
+    /// * `timeout` - How long the lane can be idle before it is evicted, if
+    ///   ever.
+    #[inline]
+    pub(crate) fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            timeout,
+            since: Instant::now(),
+            last: AtomicU64::new(0),
+        }
+    }
+
+    /// Records traffic on the lane.
+    #[inline]
+    pub(crate) fn touch(&self) {
+        let elapsed = self.since.elapsed().as_nanos();
+
+        self.last.store(
+            u64::try_from(elapsed).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Gets when the lane was created.
+    #[inline]
+    pub(crate) const fn since(&self) -> Instant {
+        self.since
+    }
+
+    /// Gets when the lane last saw traffic, or was created if it saw none.
+    #[inline]
+    pub(crate) fn last_seen(&self) -> Instant {
+        self.since + Duration::from_nanos(self.last.load(Ordering::Relaxed))
+    }
+
+    /// Gets when the lane becomes idle, unless it sees traffic until then.
+    ///
+    /// # Returns
+    /// * [`Some(deadline)`] - When the lane becomes idle.
+    /// * [`None`] - If the lane has no idle timeout.
+    #[inline]
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.timeout.map(|timeout| self.last_seen() + timeout)
+    }
+}
+
+/// The lifecycle of a lane, shared by its halves, which reports the lane's
+/// [`LaneEvent`]s to the observers of its bus.
+#[derive(Debug)]
+pub(crate) struct Lifecycle<T> {
+    tag: T,
+    reason: OnceLock<CloseReason>,
+    events: Events<T>,
+}
+
+impl<T: Clone> Lifecycle<T> {
+    /// Creates a new instance of [`Lifecycle`], emitting
+    /// [`LaneEvent::Opened`].
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    /// * `events` - The sender to emit lane events through.
+    #[inline]
+    pub(crate) fn new(tag: T, events: Events<T>) -> Self {
+        let lifecycle = Self {
+            tag,
+            reason: OnceLock::new(),
+            events,
+        };
+
+        lifecycle.emit(LaneEvent::Opened);
+        lifecycle
+    }
+
+    /// Records why the lane is being closed, emitting [`LaneEvent::Closed`]
+    /// or [`LaneEvent::Evicted`], unless it was already closed for another
+    /// reason.
+    ///
+    /// # Parameters
+    /// * `reason` - The reason the lane is being closed.
+    #[inline]
+    pub(crate) fn close(&self, reason: CloseReason) {
+        if self.reason.set(reason.clone()).is_err() {
+            return;
+        }
+
+        self.emit(|tag| match reason {
+            | CloseReason::Evicted => LaneEvent::Evicted(tag),
+            | reason => LaneEvent::Closed { tag, reason },
+        });
+    }
+
+    /// Gets the reason the lane was closed, if it was.
+    #[inline]
+    pub(crate) fn reason(&self) -> Option<&CloseReason> {
+        self.reason.get()
+    }
+
+    /// Emits an event about the lane, if anyone is observing.
+    ///
+    /// # Parameters
+    /// * `event` - Makes the event from the tag of the lane.
+    #[inline]
+    pub(crate) fn emit<F>(&self, event: F)
+    where
+        F: FnOnce(T) -> LaneEvent<T>,
+    {
+        if self.events.receiver_count() > 0 {
+            _ = self.events.send(event(self.tag.clone()));
+        }
+    }
+}
+
+/// A guard shared by all clones of a [`LaneTx`], that emits
+/// [`LaneEvent::HalfClosed`] when the last clone is dropped.
+#[derive(Debug)]
+pub(crate) struct TxCloser<T: Clone>(Arc<Lifecycle<T>>);
+
+impl<T: Clone> Drop for TxCloser<T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.0.emit(LaneEvent::HalfClosed);
+    }
+}
+
+/// The sending end of a lane's inbound channel, as held by a
+/// [`Bus`](crate::bus::Bus).
+#[derive(Debug)]
+pub(crate) struct Inlet<T: Key, V> {
+    feed: Feed<T, V>,
+    senders: Senders<T, V>,
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+impl<T: Key, V> Inlet<T, V> {
+    /// Gets the feed of the lane's inbound channel.
+    #[inline]
+    pub(crate) const fn feed(&self) -> &Feed<T, V> {
+        &self.feed
+    }
+
+    /// Makes a new sender for the lane, as long as any of its senders is
+    /// still alive.
+    #[inline]
+    pub(crate) fn sender(&self) -> Option<LaneTx<T, V>> {
+        self.senders.get().and_then(WeakLaneTx::upgrade)
+    }
+
+    /// Records why the lane is being closed, unless it was already closed
+    /// for another reason.
+    ///
+    /// # Parameters
+    /// * `reason` - The reason the lane is being closed.
+    #[inline]
+    pub(crate) fn set_reason(&self, reason: CloseReason) {
+        self.feed.lifecycle.close(reason);
+    }
+
+    /// Gets whether the lane has been idle for its idle timeout, if any.
+    ///
+    /// # Parameters
+    /// * `now` - The current time.
+    #[inline]
+    pub(crate) fn is_idle(&self, now: Instant) -> bool {
+        let deadline = self.feed.meter.activity().deadline();
+
+        deadline.is_some_and(|deadline| deadline <= now)
+    }
+
+    /// Gets whether no values are buffered or parked in the lane.
+    #[inline]
+    pub(crate) fn is_drained(&self) -> bool {
+        self.feed
+            .queue
+            .upgrade()
+            .is_none_or(|queue| lock(&queue).is_empty())
+    }
+
+    /// Gets when the lane last saw traffic.
+    #[inline]
+    pub(crate) fn last_seen(&self) -> Instant {
+        self.feed.meter.activity().last_seen()
+    }
+
+    /// Takes a snapshot of the lane's statistics.
+    #[inline]
+    pub(crate) fn stats(&self) -> LaneStats {
+        self.feed.meter.snapshot()
+    }
+
+    /// Holds the lane's reservation under the lane limit of its bus, which is
+    /// released once the lane is removed from the bus.
+    ///
+    /// # Parameters
+    /// * `permit` - The reservation, if the bus has a lane limit.
+    #[inline]
+    pub(crate) fn admitted(
+        mut self,
+        permit: Option<OwnedSemaphorePermit>,
+    ) -> Self {
+        self.permit = permit;
+        self
+    }
+
+    /// Closes the lane's inbound channel, so that pushing to it fails, even
+    /// through clones of its feed, while the values buffered or parked in it
+    /// can still be received.
+    #[inline]
+    pub(crate) fn close(&self) {
+        if let Some(queue) = self.feed.queue.upgrade() {
+            lock(&queue).close();
+        }
+    }
+
+    /// Closes the lane's inbound channel, discarding any values buffered or
+    /// parked in it.
+    #[inline]
+    pub(crate) fn discard(&self) {
+        if let Some(queue) = self.feed.queue.upgrade() {
+            let mut queue = lock(&queue);
+
+            queue.close();
+            self.feed.meter.dropped(queue.drain());
+        }
+    }
+}
+
+/// A sender of a lane's inbound channel that applies the lane's
+/// [`Overflow`] policy.
+#[derive(Debug)]
+pub(crate) struct Feed<T: Key, V> {
+    tx: Sender<Entry<V>>,
+    tag: T,
+    meter: Arc<Meter<T, V>>,
+    lifecycle: Arc<Lifecycle<T>>,
+    overflow: Overflow,
+    queue: Weak<Queue<V>>,
+    budget: Arc<ParkBudget>,
+}
+
+impl<T: Key, V> Clone for Feed<T, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            tag: self.tag.clone(),
+            meter: self.meter.clone(),
+            lifecycle: self.lifecycle.clone(),
+            overflow: self.overflow,
+            queue: self.queue.clone(),
+            budget: self.budget.clone(),
+        }
+    }
+}
+
+impl<T: Key, V> Feed<T, V> {
+    /// Gets the tag of the lane.
+    #[inline]
+    pub(crate) const fn tag(&self) -> &T {
+        &self.tag
+    }
+
+    /// Gets the overflow policy of the lane.
+    #[inline]
+    pub(crate) const fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    /// Pushes a value to the lane, waiting for room in it regardless of the
+    /// overflow policy.
+    ///
+    /// # Parameters
+    /// * `value` - The value to push.
+    #[inline]
+    pub(crate) async fn send(
+        &mut self,
+        value: V,
+    ) -> Result<(), SendError<(T, V)>> {
+        match self.tx.send(Entry::new(value)).await {
+            | Ok(()) => {
+                self.meter.received();
+                Ok(())
+            }
+            | Err(SendError(entry)) => {
+                Err(SendError((self.tag.clone(), entry.value)))
+            }
+        }
+    }
+
+    /// Pushes a value to the lane, waiting for room in it regardless of the
+    /// overflow policy, and giving up once `timeout` elapses.
+    ///
+    /// # Parameters
+    /// * `value` - The value to push.
+    /// * `timeout` - How long to wait for room in the lane.
+    #[inline]
+    pub(crate) async fn send_timeout(
+        &mut self,
+        value: V,
+        timeout: Duration,
+    ) -> Result<(), SendTimeoutError<(T, V)>> {
+        match self.tx.send_timeout(Entry::new(value), timeout).await {
+            | Ok(()) => {
+                self.meter.received();
+                Ok(())
+            }
+            | Err(SendTimeoutError::Timeout(entry)) => {
+                Err(SendTimeoutError::Timeout((self.tag.clone(), entry.value)))
+            }
+            | Err(SendTimeoutError::Closed(entry)) => {
+                Err(SendTimeoutError::Closed((self.tag.clone(), entry.value)))
+            }
+        }
+    }
+
+    /// Gets whether the lane is closed or not.
+    #[inline]
+    pub(crate) fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    /// Gets the span of the lane.
+    #[cfg(feature = "tracing")]
+    #[inline]
+    pub(crate) fn span(&self) -> &tracing::Span {
+        self.meter.span()
+    }
+
+    /// Pushes a value to the lane without waiting, applying the overflow
+    /// policy if the lane is full.
+    ///
+    /// # Parameters
+    /// * `value` - The value to push.
+    ///
+    /// # Returns
+    /// * [`Ok(true)`] - If the value was pushed.
+    /// * [`Ok(false)`] - If the value was dropped with
+    ///   [`Overflow::DropNewest`].
+    /// * [`Err(TrySendError::Full)`] - If the lane is full, and its policy
+    ///   would otherwise block or reject the value, or the park budget ran out.
+    /// * [`Err(TrySendError::Closed)`] - If the lane is closed.
+    pub(crate) fn try_push(
+        &mut self,
+        mut value: V,
+    ) -> Result<bool, TrySendError<(T, V)>> {
+        if self.overflow == Overflow::Park {
+            return self.try_park(value, None).map(|()| true);
+        }
+
+        loop {
+            let (tag, rejected) = match self.try_send(value) {
+                | Ok(()) => return Ok(true),
+                | Err(TrySendError::Full(item)) => item,
+                | Err(err) => return Err(err),
+            };
+
+            match self.overflow {
+                | Overflow::DropNewest => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(
+                        parent: self.span(),
+                        "lane is full, dropped the newest value"
+                    );
+
+                    self.lifecycle.emit(LaneEvent::Overflowed);
+                    self.meter.received();
+                    self.meter.dropped(1);
+
+                    return Ok(false);
+                }
+                | Overflow::DropOldest => {
+                    let Some(queue) = self.queue.upgrade() else {
+                        return Err(TrySendError::Closed((tag, rejected)));
+                    };
+
+                    // the receiver may have made room meanwhile, in which
+                    // case nothing is evicted
+                    if lock(&queue).try_recv().is_some() {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(
+                            parent: self.span(),
+                            "lane is full, dropped the oldest value"
+                        );
+
+                        self.lifecycle.emit(LaneEvent::Overflowed);
+                        self.meter.dropped(1);
+                    }
+                    value = rejected;
+                }
+                | Overflow::Block
+                | Overflow::Reject
+                | Overflow::Park
+                | Overflow::Unbounded => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(
+                        parent: self.span(),
+                        overflow = ?self.overflow,
+                        "lane is full"
+                    );
+
+                    self.lifecycle.emit(LaneEvent::Overflowed);
+
+                    return Err(TrySendError::Full((tag, rejected)));
+                }
+            }
+        }
+    }
+
+    /// Pushes a value to a lane with [`Overflow::Park`], parking it if the
+    /// lane is full, and waiting for room in the park budget if it ran out.
+    ///
+    /// # Parameters
+    /// * `value` - The value to push.
+    /// * `deadline` - When to give up waiting for room in the park budget, if
+    ///   ever.
+    ///
+    /// # Returns
+    /// * [`Ok(())`] - If the value was pushed or parked.
+    /// * [`Err(SendTimeoutError::Timeout)`] - If the deadline elapsed.
+    /// * [`Err(SendTimeoutError::Closed)`] - If the lane is closed.
+    pub(crate) async fn park(
+        &mut self,
+        mut value: V,
+        deadline: Option<Instant>,
+    ) -> Result<(), SendTimeoutError<(T, V)>> {
+        let mut permit = None;
+
+        loop {
+            match self.try_park(value, permit.take()) {
+                | Ok(()) => return Ok(()),
+                | Err(TrySendError::Full((_, rejected))) => value = rejected,
+                | Err(TrySendError::Closed(item)) => {
+                    return Err(SendTimeoutError::Closed(item))
+                }
+            }
+
+            let acquire = self.budget.permits().clone().acquire_owned();
+            let acquired = match deadline {
+                | Some(deadline) => {
+                    match tokio::time::timeout_at(deadline, acquire).await {
+                        | Ok(acquired) => acquired,
+                        | Err(_) => {
+                            let tag = self.tag.clone();
+
+                            return Err(SendTimeoutError::Timeout((
+                                tag, value,
+                            )));
+                        }
+                    }
+                }
+                | None => acquire.await,
+            };
+
+            // the budget is never closed
+            permit = acquired.ok();
+        }
+    }
+
+    /// Attempts to push a value to a lane with [`Overflow::Park`], parking it
+    /// if the lane is full, or if other values are already parked, so that
+    /// values are received in order.
+    ///
+    /// # Parameters
+    /// * `value` - The value to push.
+    /// * `permit` - A permit of the park budget acquired beforehand, if any.
+    ///
+    /// # Returns
+    /// * [`Ok(())`] - If the value was pushed or parked.
+    /// * [`Err(TrySendError::Full)`] - If the value had to be parked, but the
+    ///   park budget ran out.
+    /// * [`Err(TrySendError::Closed)`] - If the lane is closed.
+    fn try_park(
+        &mut self,
+        value: V,
+        permit: Option<OwnedSemaphorePermit>,
+    ) -> Result<(), TrySendError<(T, V)>> {
+        let tag = self.tag.clone();
+        let Some(queue) = self.queue.upgrade() else {
+            return Err(TrySendError::Closed((tag, value)));
+        };
+        // the queue is locked throughout, so that the receiver cannot miss a
+        // parked value
+        let mut queue = lock(&queue);
+
+        let value = if queue.parked.is_empty() {
+            match self.try_send(value) {
+                | Ok(()) => return Ok(()),
+                | Err(TrySendError::Full((_, value))) => value,
+                | Err(err) => return Err(err),
+            }
+        } else if self.tx.is_closed() {
+            return Err(TrySendError::Closed((tag, value)));
+        } else {
+            value
+        };
+        let permit = match permit {
+            | Some(permit) => permit,
+            | None => match self.budget.permits().clone().try_acquire_owned() {
+                | Ok(permit) => permit,
+                | Err(_) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(
+                        parent: self.span(),
+                        "lane is full, and the park budget ran out"
+                    );
+
+                    self.lifecycle.emit(LaneEvent::Overflowed);
+
+                    return Err(TrySendError::Full((tag, value)));
+                }
+            },
+        };
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            parent: self.span(),
+            parked = queue.parked.len() + 1,
+            "lane is full, parked the value"
+        );
+
+        self.lifecycle.emit(LaneEvent::Overflowed);
+
+        // the permit is given back once the value is received or discarded
+        permit.forget();
+        queue.parked.push_back(Entry::new(value));
+        self.meter.received();
+
+        Ok(())
+    }
+
+    /// Attempts to push a value to the lane without waiting, regardless of
+    /// the overflow policy.
+    #[inline]
+    fn try_send(&mut self, value: V) -> Result<(), TrySendError<(T, V)>> {
+        match self.tx.try_send(Entry::new(value)) {
+            | Ok(()) => {
+                self.meter.received();
+                Ok(())
+            }
+            | Err(TrySendError::Full(entry)) => {
+                Err(TrySendError::Full((self.tag.clone(), entry.value)))
+            }
+            | Err(TrySendError::Closed(entry)) => {
+                Err(TrySendError::Closed((self.tag.clone(), entry.value)))
+            }
+        }
+    }
+}
+
+/// The receiving end of a lane's inbound channel, along with the values
+/// parked with [`Overflow::Park`] while the channel was full.
+///
+/// Parked values are always newer than the values in the channel, so they are
+/// only received once the channel is drained.
+#[derive(Debug)]
+pub(crate) struct Buffer<V> {
+    rx: Receiver<Entry<V>>,
+    parked: VecDeque<Entry<V>>,
+    budget: Arc<ParkBudget>,
+    // woken once the channel is closed, as closing it does not wake the
+    // receiver while senders remain
+    waker: Option<Waker>,
+}
+
+impl<V> Buffer<V> {
+    /// Polls to receive the next value, be it buffered in the channel or
+    /// parked.
+    #[inline]
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<Entry<V>>> {
+        let polled = match self.rx.poll_recv(cx) {
+            | Poll::Ready(Some(value)) => Poll::Ready(Some(value)),
+            | polled => self.unpark().map_or(polled, |v| Poll::Ready(Some(v))),
+        };
+
+        if polled.is_pending() {
+            self.waker = Some(cx.waker().clone());
+        }
+
+        polled
+    }
+
+    /// Attempts to receive the next value without waiting.
+    #[inline]
+    fn try_recv(&mut self) -> Option<Entry<V>> {
+        self.rx.try_recv().ok().or_else(|| self.unpark())
+    }
+
+    /// Discards all buffered and parked values.
+    ///
+    /// # Returns
+    /// The number of discarded values.
+    #[inline]
+    fn drain(&mut self) -> u64 {
+        let mut drained = 0;
+
+        while self.try_recv().is_some() {
+            drained += 1;
+        }
+
+        drained
+    }
+
+    /// Gets whether no values are buffered in the channel or parked.
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.rx.is_empty() && self.parked.is_empty()
+    }
+
+    /// Closes the channel, keeping any buffered or parked values.
+    #[inline]
+    fn close(&mut self) {
+        self.rx.close();
+
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    #[inline]
+    fn unpark(&mut self) -> Option<Entry<V>> {
+        let value = self.parked.pop_front()?;
+
+        self.budget.release(1);
+
+        Some(value)
+    }
+}
+
+impl<V> Drop for Buffer<V> {
+    #[inline]
+    fn drop(&mut self) {
+        self.budget.release(self.parked.len());
+    }
+}
+
+/// A handle held by each [`LaneTx`] and [`LaneRx`] given out by a
+/// [`Bus`](crate::bus::Bus), that lets the bus wait for all of them to be
+/// dropped, and abort them.
+#[derive(Debug, Clone)]
+pub(crate) struct Tracker(watch::Receiver<bool>);
+
+impl Tracker {
+    /// Creates a new instance of [`Tracker`].
+    ///
+    /// # Parameters
+    /// * `aborted` - A receiver of whether the bus was aborted or not.
+    #[inline]
+    pub(crate) const fn new(aborted: watch::Receiver<bool>) -> Self {
+        Self(aborted)
+    }
+
+    /// Gets whether the bus was aborted or not.
+    #[inline]
+    fn is_aborted(&self) -> bool {
+        *self.0.borrow()
+    }
+}
 
 /// A single tagged lane in [`Bus`](crate::bus::Bus).
 #[derive(Debug)]
 pub struct Lane<T: Key, V> {
-    tx: LaneTx<T, V>,
+    // the receiver is dropped first, so that dropping a whole lane resets it
+    // rather than half-closing it first
     rx: LaneRx<T, V>,
+    tx: LaneTx<T, V>,
 }
 
 impl<T: Key, V> Lane<T, V> {
@@ -38,6 +953,14 @@ impl<T: Key, V> Lane<T, V> {
         &mut self.rx
     }
 
+    /// Sets the scheduling weight of the lane's outgoing messages.
+    ///
+    /// See [`LaneTx::set_weight`].
+    #[inline]
+    pub fn set_weight(&self, weight: u32) {
+        self.tx.set_weight(weight);
+    }
+
     /// Splits the lane into its sender and receiver.
     #[inline]
     pub fn split(self) -> (LaneTx<T, V>, LaneRx<T, V>) {
@@ -47,11 +970,156 @@ impl<T: Key, V> Lane<T, V> {
 
 pin_project_lite::pin_project! {
     /// A [`Lane`](crate::lane::Lane) sender half.
-    #[derive(Debug, Clone)]
+    #[derive(Debug)]
     pub struct LaneTx<T: Key, V> {
         #[pin]
         inner: Sender<(T, V)>,
         tag: T,
+        guard: Option<Arc<TxGuard<T, V>>>,
+        tracker: Option<Tracker>,
+        weight: Option<Arc<AtomicU32>>,
+        meter: Arc<Meter<T, V>>,
+        closer: Option<Arc<TxCloser<T>>>,
+    }
+}
+
+impl<T: Key, V> Clone for LaneTx<T, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            tag: self.tag.clone(),
+            guard: self.guard.clone(),
+            tracker: self.tracker.clone(),
+            weight: self.weight.clone(),
+            meter: self.meter.clone(),
+            closer: self.closer.clone(),
+        }
+    }
+}
+
+/// A weak handle to the senders of a lane, from which a new sender can be made
+/// for as long as any of them is alive.
+#[derive(Debug)]
+pub(crate) struct WeakLaneTx<T: Key, V> {
+    inner: WeakSender<(T, V)>,
+    tag: T,
+    guard: Option<Weak<TxGuard<T, V>>>,
+    weight: Option<Weak<AtomicU32>>,
+    meter: Arc<Meter<T, V>>,
+    closer: Option<Weak<TxCloser<T>>>,
+}
+
+impl<T: Key, V> WeakLaneTx<T, V> {
+    /// Makes a new sender, unless all senders of the lane were dropped.
+    ///
+    /// The new sender is not tracked by the lane's bus (see
+    /// [`LaneTx::tracked`]).
+    pub(crate) fn upgrade(&self) -> Option<LaneTx<T, V>> {
+        // each part is upgraded only if it was there to begin with
+        fn upgrade<P>(weak: &Option<Weak<P>>) -> Option<Option<Arc<P>>> {
+            match weak {
+                | Some(weak) => weak.upgrade().map(Some),
+                | None => Some(None),
+            }
+        }
+
+        Some(LaneTx {
+            inner: self.inner.upgrade()?,
+            tag: self.tag.clone(),
+            guard: upgrade(&self.guard)?,
+            tracker: None,
+            weight: upgrade(&self.weight)?,
+            meter: self.meter.clone(),
+            closer: upgrade(&self.closer)?,
+        })
+    }
+}
+
+/// A guard shared by all clones of a [`LaneTx`], that holds the lane's send
+/// credit, and emits [`Signal::TxClosed`] when the last clone is dropped.
+#[derive(Debug)]
+pub(crate) struct TxGuard<T: Key, V> {
+    tag: T,
+    hooks: Hooks<T, V>,
+    credit: Credit,
+}
+
+impl<T: Key, V> TxGuard<T, V> {
+    /// Gets the lane's send credit.
+    #[inline]
+    pub(crate) fn credits(&self) -> &Arc<Semaphore> {
+        &self.credit.permits
+    }
+
+    /// Gets the credit needed to send `value` at once.
+    ///
+    /// # Returns
+    /// * [`Some(cost)`] - The credit needed to send the value.
+    /// * [`None`] - If the value costs more than the lane's whole window, so it
+    ///   can never be sent at once.
+    #[inline]
+    pub(crate) fn cost(&self, value: &V) -> Option<u32> {
+        let cost = (self.hooks.cost)(value);
+
+        (cost <= self.hooks.window).then_some(cost)
+    }
+
+    /// Gets the credit to acquire for sending the next part of `value`.
+    ///
+    /// Values that can be split are charged as much of the credit at hand as
+    /// they cost, or up to a whole window if there is none, and then split
+    /// with [`TxGuard::split`] if they cost more.
+    ///
+    /// # Returns
+    /// * [`Some(credit)`] - The credit to acquire.
+    /// * [`None`] - If the value costs more than the lane's whole window and
+    ///   cannot be split, so it can never be sent.
+    #[inline]
+    pub(crate) fn charge(&self, value: &V) -> Option<u32> {
+        if self.hooks.split.is_none() {
+            return self.cost(value);
+        }
+
+        let at_hand = match self.credit.permits.available_permits() {
+            | 0 => self.hooks.window,
+            | permits => u32::try_from(permits).unwrap_or(u32::MAX),
+        };
+
+        Some((self.hooks.cost)(value).min(at_hand))
+    }
+
+    /// Splits off the part of `value` that was paid for with `credit`, if the
+    /// value costs more than that.
+    ///
+    /// # Returns
+    /// * [`Some(part)`] - The part to send first, while the rest of the value
+    ///   is left in `value`.
+    /// * [`None`] - If the whole value was paid for.
+    #[inline]
+    pub(crate) fn split(&self, value: &mut V, credit: u32) -> Option<V> {
+        let split = self.hooks.split?;
+
+        ((self.hooks.cost)(value) > credit).then(|| split(value, credit))
+    }
+}
+
+impl<T: Key, V> Drop for TxGuard<T, V> {
+    #[inline]
+    fn drop(&mut self) {
+        // the credit of a lane that replaced this one is left alone, and so is
+        // the lane itself
+        let current = self
+            .hooks
+            .credits
+            .remove_if(&self.tag, |_, credit| credit.id == self.credit.id)
+            .is_some();
+
+        if current {
+            let signal = Signal::TxClosed(self.tag.clone(), self.credit.id);
+
+            _ = self.hooks.signals.send(signal);
+        }
     }
 }
 
@@ -61,24 +1129,347 @@ impl<T: Key, V> LaneTx<T, V> {
     /// # Parameters
     /// * `inner` - The underlying channel sender.
     /// * `tag` - The tag of the lane.
+    /// * `meter` - The statistics of the lane.
+    #[inline]
+    pub(crate) const fn new(
+        inner: Sender<(T, V)>,
+        tag: T,
+        meter: Arc<Meter<T, V>>,
+    ) -> Self {
+        Self {
+            tag,
+            inner,
+            guard: None,
+            tracker: None,
+            weight: None,
+            meter,
+            closer: None,
+        }
+    }
+
+    /// Create a new lane sender that is controlled by `hooks`.
+    ///
+    /// The sender can only send as many values as it has credit for, and
+    /// emits [`Signal::TxClosed`] once it and all of its clones are dropped.
+    /// Its credit is its own, even if an older lane with the same tag is still
+    /// around, in which case the older lane can no longer be granted credit.
+    ///
+    /// # Parameters
+    /// * `inner` - The underlying channel sender.
+    /// * `tag` - The tag of the lane.
+    /// * `hooks` - The hooks controlling the lane.
+    /// * `meter` - The statistics of the lane.
+    #[inline]
+    pub(crate) fn guarded(
+        inner: Sender<(T, V)>,
+        tag: T,
+        hooks: &Hooks<T, V>,
+        meter: Arc<Meter<T, V>>,
+    ) -> Self {
+        let credit = Credit {
+            id: hooks.next_id.fetch_add(1, Ordering::Relaxed),
+            permits: Arc::new(Semaphore::new(hooks.window as usize)),
+        };
+
+        hooks.credits.insert(tag.clone(), credit.clone());
+
+        let guard = TxGuard {
+            tag: tag.clone(),
+            hooks: hooks.clone(),
+            credit,
+        };
+
+        Self {
+            tag,
+            inner,
+            guard: Some(Arc::new(guard)),
+            tracker: None,
+            weight: None,
+            meter,
+            closer: None,
+        }
+    }
+
+    /// Makes the sender tracked by `tracker`, so that its bus can wait for it
+    /// to be dropped, and fails any sends once the bus is aborted.
+    ///
+    /// # Parameters
+    /// * `tracker` - The tracker of the bus.
+    #[inline]
+    pub(crate) fn tracked(mut self, tracker: Tracker) -> Self {
+        self.tracker = Some(tracker);
+        self
+    }
+
+    /// Makes the sender emit [`LaneEvent::HalfClosed`] through `lifecycle`
+    /// once it and all of its clones are dropped.
+    ///
+    /// # Parameters
+    /// * `lifecycle` - The lifecycle of the lane.
+    #[inline]
+    pub(crate) fn observed(mut self, lifecycle: Arc<Lifecycle<T>>) -> Self {
+        self.closer = Some(Arc::new(TxCloser(lifecycle)));
+        self
+    }
+
+    /// Makes the sender's outgoing messages scheduled with `weight`, which is
+    /// shared by all of its clones.
+    ///
+    /// # Parameters
+    /// * `weight` - The scheduling weight of the lane.
+    #[inline]
+    pub(crate) fn weighted(mut self, weight: Arc<AtomicU32>) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    /// Sets the scheduling weight of the lane's outgoing messages, which
+    /// applies to all of its senders.
+    ///
+    /// Outgoing messages of all lanes are interleaved, where a lane with
+    /// weight `n` gets to send up to `n` messages each round (see
+    /// [`Outgoing`](crate::outgoing::Outgoing)). A weight of zero is treated
+    /// as one. Weights only apply to lanes of a [`Mux`](crate::mux::Mux)
+    /// created with [`Mux::scheduled`](crate::mux::Mux::scheduled), as the
+    /// lanes of any other mux share a single queue.
+    ///
+    /// # Parameters
+    /// * `weight` - The scheduling weight of the lane.
     #[inline]
-    pub(crate) const fn new(inner: Sender<(T, V)>, tag: T) -> Self {
-        Self { tag, inner }
+    pub fn set_weight(&self, weight: u32) {
+        if let Some(ref shared) = self.weight {
+            shared.store(weight, Ordering::Relaxed);
+        }
     }
 
     /// Sends a tagged value through the lane.
     ///
+    /// If the lane runs over a [`Transport`](crate::transport::Transport),
+    /// this waits until the remote end grants enough credit to send, and
+    /// fails right away if the value costs more than the lane's whole window.
+    /// Values that can be split, e.g. the bytes sent over yamux, are sent in
+    /// parts as credit is granted instead, in which case cancelling the send
+    /// might leave only some of the parts sent.
+    ///
     /// # Parameters
     /// * `value` - The value to send.
+    ///
+    /// # Returns
+    /// * [`Ok(())`] - If the value was sent.
+    /// * [`Err(SendError)`] - If the lane is closed, along with the part of the
+    ///   value that was not sent yet.
     #[inline]
-    pub async fn send(&mut self, value: V) -> Result<(), SendError<(T, V)>> {
-        self.inner.send((self.tag.clone(), value)).await
+    pub async fn send(
+        &mut self,
+        mut value: V,
+    ) -> Result<(), SendError<(T, V)>> {
+        if self.is_aborted() {
+            return Err(SendError((self.tag.clone(), value)));
+        }
+
+        loop {
+            // room is made for each part before it is split off, so that it
+            // is never split off in vain
+            let Ok(slot) = self.inner.reserve().await else {
+                return Err(SendError((self.tag.clone(), value)));
+            };
+            let (permit, part) = match self.guard {
+                | Some(ref guard) => {
+                    let Some(credit) = guard.charge(&value) else {
+                        return Err(SendError((self.tag.clone(), value)));
+                    };
+
+                    match guard.credits().acquire_many(credit).await {
+                        | Ok(permit) => {
+                            (Some(permit), guard.split(&mut value, credit))
+                        }
+                        | Err(_) => {
+                            return Err(SendError((self.tag.clone(), value)))
+                        }
+                    }
+                }
+                | None => (None, None),
+            };
+            let (item, rest) = match part {
+                | Some(part) => (part, Some(value)),
+                | None => (value, None),
+            };
+
+            // the credit is given back unless the value is actually sent, even
+            // if sending is cancelled
+            slot.send((self.tag.clone(), item));
+            if let Some(permit) = permit {
+                permit.forget();
+            }
+            self.meter.sent();
+
+            match rest {
+                | Some(rest) => value = rest,
+                | None => return Ok(()),
+            }
+        }
+    }
+
+    /// Attempts to send a tagged value through the lane without waiting.
+    ///
+    /// # Parameters
+    /// * `value` - The value to send.
+    ///
+    /// # Returns
+    /// * [`Ok(())`] - If the value was sent.
+    /// * [`Err(TrySendError::Full)`] - If the lane is full, or the remote end
+    ///   of a [`Transport`](crate::transport::Transport) has not granted enough
+    ///   credit.
+    /// * [`Err(TrySendError::Closed)`] - If the lane is closed, or the value
+    ///   costs more than the lane's whole window, even if it can be split (see
+    ///   [`LaneTx::send`]).
+    pub fn try_send(&mut self, value: V) -> Result<(), TrySendError<(T, V)>> {
+        if self.is_aborted() {
+            return Err(TrySendError::Closed((self.tag.clone(), value)));
+        }
+
+        let permit = match self.guard {
+            | Some(ref guard) => {
+                let Some(cost) = guard.cost(&value) else {
+                    return Err(TrySendError::Closed((
+                        self.tag.clone(),
+                        value,
+                    )));
+                };
+
+                match guard.credits().try_acquire_many(cost) {
+                    | Ok(permit) => Some(permit),
+                    | Err(TryAcquireError::NoPermits) => {
+                        return Err(TrySendError::Full((
+                            self.tag.clone(),
+                            value,
+                        )))
+                    }
+                    | Err(TryAcquireError::Closed) => {
+                        return Err(TrySendError::Closed((
+                            self.tag.clone(),
+                            value,
+                        )))
+                    }
+                }
+            }
+            | None => None,
+        };
+
+        // the credit is given back unless the value is actually sent
+        self.inner.try_send((self.tag.clone(), value))?;
+        if let Some(permit) = permit {
+            permit.forget();
+        }
+        self.meter.sent();
+
+        Ok(())
+    }
+
+    /// Sends a tagged value through the lane, giving up once `timeout`
+    /// elapses.
+    ///
+    /// # Parameters
+    /// * `value` - The value to send.
+    /// * `timeout` - How long to wait for room in the lane, and for credit from
+    ///   the remote end of a [`Transport`](crate::transport::Transport).
+    ///
+    /// # Returns
+    /// * [`Ok(())`] - If the value was sent.
+    /// * [`Err(SendTimeoutError::Timeout)`] - If the timeout elapsed.
+    /// * [`Err(SendTimeoutError::Closed)`] - If the lane is closed, or the
+    ///   value costs more than the lane's whole window and cannot be split.
+    ///
+    /// Either error carries the part of the value that was not sent yet, as
+    /// values that can be split are sent in parts (see [`LaneTx::send`]).
+    pub async fn send_timeout(
+        &mut self,
+        mut value: V,
+        timeout: Duration,
+    ) -> Result<(), SendTimeoutError<(T, V)>> {
+        if self.is_aborted() {
+            return Err(SendTimeoutError::Closed((self.tag.clone(), value)));
+        }
+
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            // room is made for each part before it is split off, so that it
+            // is never split off in vain
+            let reserve = self.inner.reserve();
+            let slot = match tokio::time::timeout_at(deadline, reserve).await {
+                | Ok(Ok(slot)) => slot,
+                | Ok(Err(_)) => {
+                    return Err(SendTimeoutError::Closed((
+                        self.tag.clone(),
+                        value,
+                    )))
+                }
+                | Err(_) => {
+                    return Err(SendTimeoutError::Timeout((
+                        self.tag.clone(),
+                        value,
+                    )))
+                }
+            };
+            let (permit, part) = match self.guard {
+                | Some(ref guard) => {
+                    let Some(credit) = guard.charge(&value) else {
+                        return Err(SendTimeoutError::Closed((
+                            self.tag.clone(),
+                            value,
+                        )));
+                    };
+                    let acquire = guard.credits().acquire_many(credit);
+
+                    match tokio::time::timeout_at(deadline, acquire).await {
+                        | Ok(Ok(permit)) => {
+                            (Some(permit), guard.split(&mut value, credit))
+                        }
+                        | Ok(Err(_)) => {
+                            return Err(SendTimeoutError::Closed((
+                                self.tag.clone(),
+                                value,
+                            )))
+                        }
+                        | Err(_) => {
+                            return Err(SendTimeoutError::Timeout((
+                                self.tag.clone(),
+                                value,
+                            )))
+                        }
+                    }
+                }
+                | None => (None, None),
+            };
+            let (item, rest) = match part {
+                | Some(part) => (part, Some(value)),
+                | None => (value, None),
+            };
+
+            // the credit is given back unless the value is actually sent
+            slot.send((self.tag.clone(), item));
+            if let Some(permit) = permit {
+                permit.forget();
+            }
+            self.meter.sent();
+
+            match rest {
+                | Some(rest) => value = rest,
+                | None => return Ok(()),
+            }
+        }
     }
 
     /// Gets whether the lane is closed or not.
     #[inline]
     pub fn is_closed(&self) -> bool {
         self.inner.is_closed()
+            || self.is_aborted()
+            || self
+                .guard
+                .as_ref()
+                .is_some_and(|guard| guard.credits().is_closed())
     }
 
     /// Gets the tag of the lane.
@@ -87,43 +1478,145 @@ impl<T: Key, V> LaneTx<T, V> {
         &self.tag
     }
 
+    /// Gets a snapshot of the lane's statistics, which are shared by both of
+    /// its halves.
+    #[inline]
+    pub fn stats(&self) -> LaneStats {
+        self.meter.snapshot()
+    }
+
+    /// Makes a weak handle to the sender, which does not keep the lane open.
+    #[inline]
+    pub(crate) fn downgrade(&self) -> WeakLaneTx<T, V> {
+        WeakLaneTx {
+            inner: self.inner.downgrade(),
+            tag: self.tag.clone(),
+            guard: self.guard.as_ref().map(Arc::downgrade),
+            weight: self.weight.as_ref().map(Arc::downgrade),
+            meter: self.meter.clone(),
+            closer: self.closer.as_ref().map(Arc::downgrade),
+        }
+    }
+
+    #[inline]
+    fn is_aborted(&self) -> bool {
+        self.tracker.as_ref().is_some_and(Tracker::is_aborted)
+    }
+
     #[inline(always)]
-    pub(crate) fn into_inner(self) -> (T, Sender<(T, V)>) {
-        (self.tag, self.inner)
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn into_inner(
+        self,
+    ) -> (
+        T,
+        Sender<(T, V)>,
+        Option<Arc<TxGuard<T, V>>>,
+        Option<Arc<TxCloser<T>>>,
+    ) {
+        (self.tag, self.inner, self.guard, self.closer)
     }
 }
 
 /// A [`Lane`](crate::lane::Lane) receiver half.
 #[derive(Debug)]
 pub struct LaneRx<T: Key, V> {
-    inner: Receiver<(T, V)>,
+    inner: Arc<Queue<V>>,
     tx_slot: LaneTxSlot<T, V>,
+    lifecycle: Arc<Lifecycle<T>>,
+    senders: Senders<T, V>,
+    hooks: Option<RxHooks<T, V>>,
+    tracker: Tracker,
+    meter: Arc<Meter<T, V>>,
+    idle: Option<Pin<Box<Sleep>>>,
+}
+
+/// The state of a [`LaneRx`] that is controlled by [`Hooks`].
+#[derive(Debug)]
+struct RxHooks<T: Key, V> {
+    hooks: Hooks<T, V>,
+    #[cfg(feature = "transport")]
+    consumed: u32,
+    #[cfg(feature = "transport")]
+    threshold: u32,
 }
 
 impl<T: Key, V> LaneRx<T, V> {
-    /// Create a new lane receiver.
+    /// Create a new lane, returning the receiver along with the inlet
+    /// through which values are pushed to it.
     ///
     /// # Parameters
-    /// * `inner` - The underlying channel receiver.
-    /// * `tx_slot` - The slot in the map for the lane's sender.
+    /// * `tx` - The underlying channel sender.
+    /// * `rx` - The underlying channel receiver.
+    /// * `tx_slot` - The slot in the map for the lane's inlet.
+    /// * `hooks` - Optional hooks to notify as values are consumed, and once
+    ///   the receiver is closed or dropped.
+    /// * `tracker` - The tracker of the bus the lane belongs to.
+    /// * `overflow` - The overflow policy applied by the inlet.
+    /// * `budget` - The budget of values that can be parked with
+    ///   [`Overflow::Park`], shared by all lanes of the bus.
+    /// * `meter` - The statistics of the lane.
+    /// * `lifecycle` - The lifecycle of the lane.
     #[inline]
-    pub(crate) const fn new(
-        inner: Receiver<(T, V)>,
-        tx_slot: MapSlot<T, LaneTx<T, V>>,
-    ) -> Self {
-        Self { inner, tx_slot }
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        tx: Sender<Entry<V>>,
+        rx: Receiver<Entry<V>>,
+        tx_slot: LaneTxSlot<T, V>,
+        hooks: Option<&Hooks<T, V>>,
+        tracker: Tracker,
+        overflow: Overflow,
+        budget: Arc<ParkBudget>,
+        meter: Arc<Meter<T, V>>,
+        lifecycle: Arc<Lifecycle<T>>,
+    ) -> (Self, Inlet<T, V>) {
+        let hooks = hooks.map(|hooks| RxHooks {
+            hooks: hooks.clone(),
+            #[cfg(feature = "transport")]
+            consumed: 0,
+            // credit is granted back in batches of half the window
+            #[cfg(feature = "transport")]
+            threshold: hooks.window.div_ceil(2),
+        });
+        let senders = Senders::default();
+        let queue = Arc::new(Mutex::new(Buffer {
+            rx,
+            parked: VecDeque::new(),
+            budget: budget.clone(),
+            waker: None,
+        }));
+        let inlet = Inlet {
+            feed: Feed {
+                tx,
+                tag: tx_slot.key().clone(),
+                meter: meter.clone(),
+                lifecycle: lifecycle.clone(),
+                overflow,
+                queue: Arc::downgrade(&queue),
+                budget,
+            },
+            senders: senders.clone(),
+            permit: None,
+        };
+        let rx = Self {
+            inner: queue,
+            tx_slot,
+            lifecycle,
+            senders,
+            hooks,
+            tracker,
+            meter,
+            idle: None,
+        };
+
+        (rx, inlet)
     }
 
     /// Receives a tagged value from the lane.
+    ///
+    /// See [`LaneRx::recv_result`] to know why the lane was closed.
     #[inline]
     pub async fn recv(&mut self) -> Option<V> {
-        if self.is_closed() {
-            return None;
-        }
-
-        let value = self.inner.recv().await;
-
-        self.map_value(value)
+        self.recv_result().await.ok()
     }
 
     /// Polls to receive the next message on this channel.
@@ -132,11 +1625,42 @@ impl<T: Key, V> LaneRx<T, V> {
     where
         T: Unpin,
     {
-        if self.is_closed() {
-            return Poll::Ready(None);
+        self.poll_recv_result(cx).map(Result::ok)
+    }
+
+    /// Receives a tagged value from the lane, or the reason the lane was
+    /// closed.
+    ///
+    /// # Returns
+    /// * [`Ok(value)`] - The received value.
+    /// * [`Err(reason)`] - If the lane was closed, and all values already sent
+    ///   to it were received.
+    #[inline]
+    pub async fn recv_result(&mut self) -> Result<V, CloseReason> {
+        if self.is_closed() || self.abort() {
+            return Err(self.reason());
+        }
+
+        poll_fn(|cx| self.poll_value(cx)).await
+    }
+
+    /// Polls to receive the next message on this channel, or the reason the
+    /// lane was closed.
+    ///
+    /// See [`LaneRx::recv_result`].
+    #[inline]
+    pub fn poll_recv_result(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<V, CloseReason>>
+    where
+        T: Unpin,
+    {
+        if self.is_closed() || self.abort() {
+            return Poll::Ready(Err(self.reason()));
         }
 
-        self.inner.poll_recv(cx).map(|v| self.map_value(v))
+        self.poll_value(cx)
     }
 
     /// Gets whether the lane is closed or not.
@@ -150,9 +1674,17 @@ impl<T: Key, V> LaneRx<T, V> {
     /// Any values already sent can still be received.
     #[inline]
     pub fn close(&mut self) {
-        if !self.is_closed() {
-            self.inner.close();
-            self.tx_slot.manual_drop();
+        if self.is_closed() {
+            // all values were received once the lane ended on its own
+            self.lifecycle.close(CloseReason::Graceful);
+        } else {
+            self.lifecycle.close(CloseReason::LocalClose);
+            lock(&self.inner).close();
+            self.unregister();
+        }
+
+        if let Some(rx) = self.hooks.take() {
+            _ = rx.hooks.signals.send(Signal::RxClosed(self.tag().clone()));
         }
     }
 
@@ -162,17 +1694,151 @@ impl<T: Key, V> LaneRx<T, V> {
         self.tx_slot.key()
     }
 
+    /// Gets a snapshot of the lane's statistics, which are shared by both of
+    /// its halves.
+    #[inline]
+    pub fn stats(&self) -> LaneStats {
+        self.meter.snapshot()
+    }
+
+    /// Gets the statistics of the lane.
+    #[inline]
+    pub(crate) const fn meter(&self) -> &Arc<Meter<T, V>> {
+        &self.meter
+    }
+
+    /// Gets the lifecycle of the lane.
+    #[inline]
+    pub(crate) const fn lifecycle(&self) -> &Arc<Lifecycle<T>> {
+        &self.lifecycle
+    }
+
+    /// Gets the senders of the lane, once it has any.
+    #[inline]
+    pub(crate) const fn senders(&self) -> &Senders<T, V> {
+        &self.senders
+    }
+
+    /// Polls to receive the next value, closing the lane once it has been
+    /// idle for its idle timeout.
+    ///
+    /// With the `tracing` feature, this is polled within the lane's span.
+    fn poll_value(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<V, CloseReason>> {
+        #[cfg(feature = "tracing")]
+        let span = self.meter.span().clone();
+        #[cfg(feature = "tracing")]
+        let _entered = span.enter();
+
+        let polled = lock(&self.inner).poll_recv(cx);
+
+        if let Poll::Ready(value) = polled {
+            return Poll::Ready(self.map_value(value));
+        }
+
+        let Some(deadline) = self.meter.activity().deadline() else {
+            return Poll::Pending;
+        };
+        let idle = self.idle.get_or_insert_with(|| {
+            Box::pin(tokio::time::sleep_until(deadline))
+        });
+
+        // the deadline moves along with the lane's traffic
+        if idle.deadline() != deadline {
+            idle.as_mut().reset(deadline);
+        }
+
+        ready!(idle.as_mut().poll(cx));
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("lane is idle, closing it");
+
+        self.lifecycle.close(CloseReason::Idle);
+        self.close();
+
+        Poll::Ready(Err(self.reason()))
+    }
+
+    /// Removes the lane's inlet from its bus, unless the bus already replaced
+    /// it with the inlet of a newer lane with the same tag.
+    #[inline]
+    fn unregister(&mut self) {
+        let queue = Arc::as_ptr(&self.inner);
+
+        self.tx_slot
+            .manual_drop_if(|inlet| Weak::as_ptr(&inlet.feed.queue) == queue);
+    }
+
+    /// Closes the lane, discarding any buffered values, if its bus was
+    /// aborted.
+    ///
+    /// # Returns
+    /// Whether the bus was aborted or not.
+    #[inline]
+    fn abort(&mut self) -> bool {
+        let aborted = self.tracker.is_aborted();
+
+        if aborted {
+            self.lifecycle.close(CloseReason::Shutdown);
+            self.close();
+            let drained = lock(&self.inner).drain();
+
+            self.meter.dropped(drained);
+        }
+
+        aborted
+    }
+
+    /// Gets the reason the lane was closed, assuming it was.
+    #[inline]
+    fn reason(&self) -> CloseReason {
+        self.lifecycle
+            .reason()
+            .cloned()
+            .unwrap_or(CloseReason::Graceful)
+    }
+
     #[inline]
-    fn map_value(&mut self, value: Option<(T, V)>) -> Option<V> {
-        match value {
-            | Some((tag, value)) => {
-                debug_assert_eq!(self.tag(), &tag);
-                Some(value)
+    fn map_value(&mut self, entry: Option<Entry<V>>) -> Result<V, CloseReason> {
+        match entry {
+            | Some(Entry { value, at }) => {
+                self.meter.consumed(at);
+
+                #[cfg(feature = "transport")]
+                if let Some(ref mut rx) = self.hooks {
+                    rx.consumed += rx.hooks.cost(&value);
+
+                    if rx.consumed >= rx.threshold {
+                        let consumed = std::mem::take(&mut rx.consumed);
+                        let tag = self.tx_slot.key().clone();
+                        let signal = Signal::Consumed(tag, consumed);
+
+                        _ = rx.hooks.signals.send(signal);
+                    }
+                }
+
+                Ok(value)
             }
             | None => {
-                self.tx_slot.manual_drop();
-                None
+                self.unregister();
+                Err(self.reason())
             }
         }
     }
 }
+
+impl<T: Key, V> Drop for LaneRx<T, V> {
+    #[inline]
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Locks a lane's queue, ignoring poisoning as the queue is never left in an
+/// inconsistent state.
+#[inline]
+fn lock<V>(queue: &Queue<V>) -> MutexGuard<'_, Buffer<V>> {
+    queue.lock().unwrap_or_else(|err| err.into_inner())
+}
@@ -1,16 +1,57 @@
+pub mod admission;
 pub mod bus;
+pub mod event;
+pub mod fanout;
+pub mod group;
+pub mod incoming;
 pub mod lane;
 pub mod map;
 pub mod mux;
+pub mod outgoing;
+pub mod overflow;
+pub mod stats;
+pub mod tag;
+pub mod topic;
 
 #[doc(inline)]
-pub use bus::Bus;
+pub use admission::{AdmissionStats, AtCapacity};
 #[doc(inline)]
-pub use lane::{Lane, LaneRx, LaneTx};
+pub use bus::{Bus, Delivery, DeliveryReport, Route};
+#[doc(inline)]
+pub use event::{LaneEvent, LaneEvents};
+#[doc(inline)]
+pub use fanout::FanoutRx;
+#[doc(inline)]
+pub use group::{Assignment, ConsumerGroup, Worker};
+#[doc(inline)]
+pub use incoming::Incoming;
+#[doc(inline)]
+pub use lane::{CloseReason, Lane, LaneRx, LaneTx};
 #[doc(inline)]
 pub use map::{Key, Map};
 #[doc(inline)]
-pub use mux::Mux;
+pub use mux::{Mux, MuxError, OpenError};
+#[doc(inline)]
+pub use outgoing::Outgoing;
+#[doc(inline)]
+pub use overflow::Overflow;
+#[doc(inline)]
+pub use stats::{LaneStats, MuxStats};
+#[doc(inline)]
+pub use tag::{Parity, TagAllocator};
+#[doc(inline)]
+pub use topic::{Pattern, Subscription, TopicBus};
 
+#[cfg(feature = "codec")]
+pub mod codec;
+#[cfg(feature = "util")]
+pub mod rpc;
 #[cfg(feature = "util")]
 pub mod util;
+
+#[cfg(feature = "util")]
+#[doc(inline)]
+pub use rpc::{CallError, Client, Responder, Server};
+
+#[cfg(feature = "transport")]
+pub mod transport;
@@ -2,6 +2,7 @@ use std::fmt::Debug;
 use std::hash::Hash;
 use std::sync::Arc;
 
+use dashmap::iter::Iter;
 use dashmap::mapref::one::RefMut;
 use dashmap::DashMap;
 
@@ -59,7 +60,7 @@ impl<K: Key, V> Map<K, V> {
     ///
     /// # Returns
     /// * [`MapSlot<K, V>`] - An object that removes the inserted or replaced
-    ///  item from the map when dropped.
+    ///   item from the map when dropped.
     #[inline]
     pub(crate) fn get_or_insert<F>(&self, key: K, with: F) -> RefMut<'_, K, V>
     where
@@ -70,6 +71,21 @@ impl<K: Key, V> Map<K, V> {
             .or_insert_with(|| with(MapSlot::new(self.0.clone(), key)))
     }
 
+    /// Inserts an item with `key` and `value` into the map, replacing any
+    /// existing item with the same `key`.
+    ///
+    /// # Parameters
+    /// * `key` - The key identifying the item to insert.
+    /// * `value` - The value to insert.
+    ///
+    /// # Returns
+    /// * [`Some(V)`] - The replaced value, if any.
+    /// * [`None`] - If no item was found with the same `key`.
+    #[inline]
+    pub(crate) fn insert(&self, key: K, value: V) -> Option<V> {
+        self.0.insert(key, value)
+    }
+
     /// Removes the item identified with `key` from the map.
     ///
     /// # Parameters
@@ -79,6 +95,38 @@ impl<K: Key, V> Map<K, V> {
         self.0.remove(key)
     }
 
+    /// Removes the item identified with `key` from the map, only if `remove`
+    /// returns `true` for it.
+    ///
+    /// # Parameters
+    /// * `key` - The key identifying the item to remove.
+    /// * `remove` - The predicate deciding whether to remove the item or not.
+    #[inline]
+    pub(crate) fn remove_if<F>(&self, key: &K, remove: F) -> Option<(K, V)>
+    where
+        F: FnOnce(&K, &V) -> bool,
+    {
+        self.0.remove_if(key, remove)
+    }
+
+    /// Retains only the items for which `keep` returns `true`.
+    ///
+    /// # Parameters
+    /// * `keep` - The predicate deciding whether to keep an item or not.
+    #[inline]
+    pub(crate) fn retain<F>(&self, keep: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.0.retain(keep);
+    }
+
+    /// Iterates over the items of the map.
+    #[inline]
+    pub(crate) fn iter(&self) -> Iter<'_, K, V> {
+        self.0.iter()
+    }
+
     /// Clears the map, removing all items.
     #[inline]
     pub fn clear(&self) {
@@ -141,6 +189,22 @@ impl<K: Key, V> MapSlot<K, V> {
             map.remove(&self.key);
         }
     }
+
+    /// Eagerly removes the item associated with this slot from the map, if it
+    /// still exists and `is_same` holds for it.
+    ///
+    /// # Parameters
+    /// * `is_same` - Whether the item in the map is still the one this slot was
+    ///   made for, rather than one inserted since with the same key.
+    #[inline]
+    pub(crate) fn manual_drop_if<F>(&mut self, is_same: F)
+    where
+        F: FnOnce(&V) -> bool,
+    {
+        if let Some(map) = self.map.take() {
+            map.remove_if(&self.key, |_, value| is_same(value));
+        }
+    }
 }
 
 impl<K: Key, V> Drop for MapSlot<K, V> {
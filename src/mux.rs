@@ -1,13 +1,166 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use dashmap::DashMap;
 use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::{SendTimeoutError, TrySendError};
+use tokio::time::Instant;
 
-use crate::{Bus, Key, Lane, LaneTx};
+use crate::admission::{AdmissionStats, AtCapacity};
+use crate::bus::Delivery;
+use crate::fanout::{Fanout, Fanouts, DEFAULT_FANOUT_BUF};
+use crate::group::{Assignment, ConsumerGroup};
+use crate::lane::Hooks;
+use crate::outgoing::{Outlets, DEFAULT_WEIGHT};
+use crate::stats::MuxStats;
+use crate::{
+    Bus,
+    DeliveryReport,
+    FanoutRx,
+    Incoming,
+    Key,
+    Lane,
+    LaneEvents,
+    LaneRx,
+    LaneTx,
+    Outgoing,
+    Overflow,
+    Route,
+    TagAllocator,
+};
 
 /// A multiplexer that allows for multiple senders and receivers act on a single
 /// stream of messages.
 #[derive(Debug)]
 pub struct Mux<T: Key, V> {
     bus: Bus<T, V>,
-    tx: mpsc::Sender<(T, V)>,
+    outlets: Outlets<T, V>,
+    weight: u32,
+    weights: DashMap<T, u32>,
+    hooks: Option<Hooks<T, V>>,
+    incoming: Option<mpsc::Sender<Lane<T, V>>>,
+    allocator: Allocator<T>,
+    fanouts: Fanouts<T, V>,
+    fanout_buf: usize,
+}
+
+/// An error returned when opening a lane fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpenError<T> {
+    /// A lane with the given tag is already open.
+    InUse(T),
+    /// No tag could be allocated, either because no
+    /// [`TagAllocator`](crate::tag::TagAllocator) is set, or because it ran
+    /// out of tags.
+    Exhausted,
+    /// The lane was refused by the lane limit or creation rate (see
+    /// [`Mux::with_max_lanes`](crate::mux::Mux::with_max_lanes)).
+    Refused(T),
+}
+
+impl<T: fmt::Debug> fmt::Display for OpenError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            | OpenError::InUse(tag) => {
+                write!(f, "lane {tag:?} is already open")
+            }
+            | OpenError::Exhausted => f.write_str("no more tags available"),
+            | OpenError::Refused(tag) => {
+                write!(f, "lane {tag:?} was refused by admission control")
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for OpenError<T> {}
+
+/// An error returned when a message could not be delivered to a lane, giving
+/// back the undelivered tag and message.
+#[derive(Clone, PartialEq, Eq)]
+pub enum MuxError<T, V> {
+    /// A new lane was created for the message, but refused it.
+    Rejected(T, V),
+    /// The mux was shut down, so no lane can receive the message.
+    Shutdown(T, V),
+    /// The lane kept being closed while delivering the message, and the retry
+    /// budget ran out (see
+    /// [`Mux::with_retries`](crate::mux::Mux::with_retries)).
+    Exhausted(T, V),
+    /// The lane is full (see [`Mux::try_send`](crate::mux::Mux::try_send) and
+    /// [`Overflow::Reject`](crate::overflow::Overflow::Reject)).
+    Full(T, V),
+    /// The lane stayed full until the timeout elapsed (see
+    /// [`Mux::send_timeout`](crate::mux::Mux::send_timeout)).
+    Timeout(T, V),
+    /// A new lane was needed for the message, but was refused by the lane
+    /// limit or creation rate (see
+    /// [`Mux::with_max_lanes`](crate::mux::Mux::with_max_lanes)).
+    Refused(T, V),
+}
+
+impl<T, V> MuxError<T, V> {
+    /// Gets the undelivered tag and message back.
+    #[inline]
+    pub fn into_inner(self) -> (T, V) {
+        match self {
+            | MuxError::Rejected(tag, value)
+            | MuxError::Shutdown(tag, value)
+            | MuxError::Exhausted(tag, value)
+            | MuxError::Full(tag, value)
+            | MuxError::Timeout(tag, value)
+            | MuxError::Refused(tag, value) => (tag, value),
+        }
+    }
+}
+
+impl<T: fmt::Debug, V> fmt::Debug for MuxError<T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, tag) = match self {
+            | MuxError::Rejected(tag, _) => ("Rejected", tag),
+            | MuxError::Shutdown(tag, _) => ("Shutdown", tag),
+            | MuxError::Exhausted(tag, _) => ("Exhausted", tag),
+            | MuxError::Full(tag, _) => ("Full", tag),
+            | MuxError::Timeout(tag, _) => ("Timeout", tag),
+            | MuxError::Refused(tag, _) => ("Refused", tag),
+        };
+
+        f.debug_tuple(name).field(tag).finish_non_exhaustive()
+    }
+}
+
+impl<T: fmt::Debug, V> fmt::Display for MuxError<T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            | MuxError::Rejected(tag, _) => {
+                write!(f, "lane {tag:?} rejected the message")
+            }
+            | MuxError::Shutdown(tag, _) => {
+                write!(f, "mux was shut down before delivering to lane {tag:?}")
+            }
+            | MuxError::Exhausted(tag, _) => {
+                write!(f, "ran out of retries delivering to lane {tag:?}")
+            }
+            | MuxError::Full(tag, _) => write!(f, "lane {tag:?} is full"),
+            | MuxError::Timeout(tag, _) => {
+                write!(f, "timed out delivering to lane {tag:?}")
+            }
+            | MuxError::Refused(tag, _) => {
+                write!(f, "lane {tag:?} was refused by admission control")
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug, V> std::error::Error for MuxError<T, V> {}
+
+/// The [`TagAllocator`] of a [`Mux`], if any.
+struct Allocator<T>(Mutex<Option<Box<dyn TagAllocator<T>>>>);
+
+impl<T> fmt::Debug for Allocator<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Allocator").finish_non_exhaustive()
+    }
 }
 
 impl<T: Key, V> Mux<T, V> {
@@ -23,124 +176,1833 @@ impl<T: Key, V> Mux<T, V> {
     #[inline]
     pub fn new(buf: usize, lane_buf: usize) -> (Self, mpsc::Receiver<(T, V)>) {
         let (tx, rx) = mpsc::channel(buf);
-        let mux = Self {
-            bus: Bus::new(lane_buf),
-            tx,
-        };
+        let mux =
+            Self::from_parts(Bus::new(lane_buf), Outlets::Shared(tx), None);
 
         (mux, rx)
     }
 
+    /// Create a new [`Mux`](crate::mux::Mux) whose lanes each have their own
+    /// outgoing messages queue, interleaved with deficit round robin so that a
+    /// chatty lane cannot starve the others.
+    ///
+    /// # Parameters
+    /// * `buf` - The buffer size of each lane's outgoing messages queue.
+    /// * `lane_buf` - The buffer size of each lane.
+    ///
+    /// # Returns
+    /// A new [`Mux`](crate::mux::Mux) and an [`Outgoing`] that aggregates
+    /// outgoing messages from all lanes.
+    #[inline]
+    pub fn scheduled(buf: usize, lane_buf: usize) -> (Self, Outgoing<T, V>) {
+        let (outlets, rx) = Outgoing::new(buf);
+
+        (Self::from_parts(Bus::new(lane_buf), outlets, None), rx)
+    }
+
+    /// Create a new scheduled [`Mux`](crate::mux::Mux) (see
+    /// [`Mux::scheduled`](crate::mux::Mux::scheduled)) whose lanes are
+    /// controlled by `hooks`.
+    ///
+    /// # Parameters
+    /// * `buf` - The buffer size of each lane's outgoing messages queue.
+    /// * `lane_buf` - The buffer size of each lane.
+    /// * `hooks` - The hooks controlling the lanes.
+    #[cfg(feature = "transport")]
+    #[inline]
+    pub(crate) fn with_hooks(
+        buf: usize,
+        lane_buf: usize,
+        hooks: Hooks<T, V>,
+    ) -> (Self, Outgoing<T, V>) {
+        let (outlets, rx) = Outgoing::new(buf);
+        let bus = Bus::with_hooks(lane_buf, hooks.clone());
+
+        (Self::from_parts(bus, outlets, Some(hooks)), rx)
+    }
+
+    #[inline]
+    fn from_parts(
+        bus: Bus<T, V>,
+        outlets: Outlets<T, V>,
+        hooks: Option<Hooks<T, V>>,
+    ) -> Self {
+        Self {
+            bus,
+            outlets,
+            weight: DEFAULT_WEIGHT,
+            weights: DashMap::new(),
+            hooks,
+            incoming: None,
+            allocator: Allocator(Mutex::new(None)),
+            fanouts: Default::default(),
+            fanout_buf: DEFAULT_FANOUT_BUF,
+        }
+    }
+
     /// Send a message to a lane.
     ///
     /// # Parameters
     /// * `tag` - The tag of the lane.
     /// * `value` - The message to send.
     ///
+    /// If the message is sent to a new lane, and the lane is delivered to
+    /// [`Incoming`](crate::incoming::Incoming), this waits until there is
+    /// room for the lane in its backlog.
+    ///
     /// # Returns
-    /// * [`Ok(None)`] - If the message is sent to an existing lane.
-    /// * [`Ok(Some(lane))`] - If the message is sent to a new lane, the new
-    ///  lane  is returned.
-    /// * [`Err(SendError((tag, value)))`] - If the lane associated with the
-    ///   given tag is closed. closed.
+    /// * [`Ok(Delivery::Existing)`] - If the message is sent to an existing
+    ///   lane.
+    /// * [`Ok(Delivery::New(lane))`] - If the message is sent to a new lane,
+    ///   but nobody is accepting lanes through
+    ///   [`Mux::incoming`](crate::mux::Mux::incoming).
+    /// * [`Ok(Delivery::Accepted)`] - If the message is sent to a new lane that
+    ///   was delivered to [`Incoming`](crate::incoming::Incoming).
+    /// * [`Err(MuxError)`] - If the message could not be delivered, along with
+    ///   the undelivered tag and message.
     #[inline]
-    pub async fn send(&mut self, tag: T, value: V) -> Option<Lane<T, V>> {
-        self.bus.push(tag, value).await.map(|rx| {
-            let tx = LaneTx::new(self.tx.clone(), rx.tag().clone());
-
-            Lane::from_parts(tx, rx)
-        })
+    pub async fn send(
+        &mut self,
+        tag: T,
+        value: V,
+    ) -> Result<Delivery<Lane<T, V>>, MuxError<T, V>> {
+        self.deliver(tag, value).await
     }
 
-    /// Close all lanes.
+    /// Attempts to send a message to a lane without waiting.
+    ///
+    /// If the message is sent to a new lane, but the backlog of
+    /// [`Incoming`](crate::incoming::Incoming) is full, the lane is returned
+    /// instead of waiting for room in the backlog.
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    /// * `value` - The message to send.
+    ///
+    /// # Returns
+    /// Same as [`Mux::send`](crate::mux::Mux::send), or
+    /// [`Err(MuxError::Full)`] if the lane is full.
     #[inline]
-    pub fn close(self) {
-        drop(self)
+    pub fn try_send(
+        &mut self,
+        tag: T,
+        value: V,
+    ) -> Result<Delivery<Lane<T, V>>, MuxError<T, V>> {
+        self.try_deliver(tag, value)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use fake::{Fake, Faker};
-    use rand::rngs::StdRng;
-    use rand::SeedableRng;
+    /// Sends a message to a lane, giving up once `timeout` elapses.
+    ///
+    /// If the message is sent to a new lane, but the backlog of
+    /// [`Incoming`](crate::incoming::Incoming) stays full until the timeout
+    /// elapses, the lane is returned instead.
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    /// * `value` - The message to send.
+    /// * `timeout` - How long to wait for room in the lane.
+    ///
+    /// # Returns
+    /// Same as [`Mux::send`](crate::mux::Mux::send), or
+    /// [`Err(MuxError::Timeout)`] if the timeout elapsed.
+    pub async fn send_timeout(
+        &mut self,
+        tag: T,
+        value: V,
+        timeout: Duration,
+    ) -> Result<Delivery<Lane<T, V>>, MuxError<T, V>> {
+        let deadline = Instant::now() + timeout;
+        let rx = match self
+            .bus
+            .push_timeout(tag, value, timeout)
+            .await?
+            .into_lane()
+        {
+            | Ok(rx) => rx,
+            | Err(delivery) => return Ok(delivery),
+        };
+        let lane = self.lane(rx);
+        let timeout = deadline.saturating_duration_since(Instant::now());
 
-    use super::*;
+        Ok(match self.incoming {
+            | Some(ref incoming) => {
+                match incoming.send_timeout(lane, timeout).await {
+                    | Ok(()) => Delivery::Accepted,
+                    | Err(
+                        SendTimeoutError::Timeout(lane)
+                        | SendTimeoutError::Closed(lane),
+                    ) => Delivery::New(lane),
+                }
+            }
+            | None => Delivery::New(lane),
+        })
+    }
 
-    #[tokio::test]
-    async fn mux_test() {
-        let buf: usize = (1..32).fake();
-        let lane_buf: usize = (1..8).fake();
-        let lane_cnt: u64 = 1000;
-        let msg_cnt: u64 = 1000;
+    /// Sets the overflow policy of lanes, i.e. what sending to a lane whose
+    /// buffer is full does.
+    ///
+    /// # Parameters
+    /// * `overflow` - The overflow policy, [`Overflow::Block`] by default.
+    #[inline]
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.bus.set_overflow(overflow);
+        self
+    }
 
-        let (mut mux_tx, mut mux_rx) = Mux::new(buf, lane_buf);
+    /// Sets the overflow policy of the lane with the given tag, overriding
+    /// the one set with
+    /// [`Mux::with_overflow`](crate::mux::Mux::with_overflow).
+    ///
+    /// This takes effect once the lane is created, so a lane that already
+    /// exists keeps its policy until it is closed.
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    /// * `overflow` - The overflow policy.
+    #[inline]
+    pub fn set_lane_overflow(&self, tag: T, overflow: Overflow) {
+        self.bus.set_lane_overflow(tag, overflow);
+    }
 
-        let pull = tokio::spawn(async move {
-            for lane_no in 0..lane_cnt {
-                let rng = &mut get_rng(lane_no);
+    /// Sets how many messages can be parked across all lanes with
+    /// [`Overflow::Park`], before sending to a full lane waits.
+    ///
+    /// # Parameters
+    /// * `budget` - The park budget, [`DEFAULT_PARK_BUDGET`] by default.
+    ///
+    /// [`DEFAULT_PARK_BUDGET`]: crate::bus::DEFAULT_PARK_BUDGET
+    #[inline]
+    pub fn with_park_budget(self, budget: usize) -> Self {
+        self.bus.set_park_budget(budget);
+        self
+    }
 
-                for msg_no in 0..msg_cnt {
-                    let (actual_tag, actual_msg) = mux_rx.recv().await.unwrap();
+    /// Sets the scheduling weight of lanes' outgoing messages.
+    ///
+    /// See [`LaneTx::set_weight`](crate::lane::LaneTx::set_weight).
+    ///
+    /// # Parameters
+    /// * `weight` - The scheduling weight, [`DEFAULT_WEIGHT`] by default.
+    ///
+    /// [`DEFAULT_WEIGHT`]: crate::outgoing::DEFAULT_WEIGHT
+    #[inline]
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
 
-                    assert_eq!(lane_no, actual_tag);
+    /// Sets the scheduling weight of the lane with the given tag, overriding
+    /// the one set with [`Mux::with_weight`](crate::mux::Mux::with_weight).
+    ///
+    /// This takes effect once the lane is created, so a lane that already
+    /// exists keeps its weight, which can be changed with
+    /// [`LaneTx::set_weight`](crate::lane::LaneTx::set_weight) instead.
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    /// * `weight` - The scheduling weight.
+    #[inline]
+    pub fn set_lane_weight(&self, tag: T, weight: u32) {
+        self.weights.insert(tag, weight);
+    }
 
-                    let (actual_msg_no, actual_msg): (u64, String) = actual_msg;
-                    let expected_msg: String = Faker.fake_with_rng(rng);
+    /// Closes lanes that see no traffic in either direction for `timeout`,
+    /// reporting [`CloseReason::Idle`](crate::lane::CloseReason::Idle) to
+    /// their receivers.
+    ///
+    /// A lane whose receiver is waiting for values is closed as soon as it
+    /// becomes idle. Lanes whose receivers are left unattended are looked for
+    /// whenever values are sent through the mux or lanes are opened, at most
+    /// once per the shortest idle timeout, so they are closed within twice
+    /// their idle timeout as long as the mux sees traffic, or right away by
+    /// [`Mux::evict_idle`](crate::mux::Mux::evict_idle).
+    ///
+    /// # Parameters
+    /// * `timeout` - The idle timeout. Lanes are never closed for being idle by
+    ///   default.
+    #[inline]
+    pub fn with_idle_timeout(self, timeout: Duration) -> Self {
+        self.bus.set_idle_timeout(Some(timeout));
+        self
+    }
 
-                    assert_eq!(msg_no, actual_msg_no);
-                    assert_eq!(expected_msg, actual_msg);
-                }
-            }
+    /// Sets the idle timeout of the lane with the given tag, overriding the
+    /// one set with
+    /// [`Mux::with_idle_timeout`](crate::mux::Mux::with_idle_timeout).
+    ///
+    /// This takes effect once the lane is created, so a lane that already
+    /// exists keeps its idle timeout until it is closed.
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    /// * `timeout` - The idle timeout.
+    #[inline]
+    pub fn set_lane_idle_timeout(&self, tag: T, timeout: Duration) {
+        self.bus.set_lane_idle_timeout(tag, timeout);
+    }
 
-            assert_eq!(None, mux_rx.recv().await);
-        });
+    /// Closes lanes that saw no traffic in either direction for their idle
+    /// timeout, discarding any values buffered in them.
+    ///
+    /// # Returns
+    /// The number of closed lanes.
+    #[inline]
+    pub fn evict_idle(&self) -> usize {
+        self.bus.evict_idle()
+    }
 
-        for lane_no in 0..lane_cnt {
-            let rng = &mut get_rng(lane_no);
+    /// Sets the maximum number of lanes that can be open at once, e.g. so that
+    /// a peer sending to many distinct tags cannot exhaust memory.
+    ///
+    /// Once the limit is reached, new lanes are either refused with
+    /// [`MuxError::Refused`](crate::mux::MuxError::Refused) and
+    /// [`OpenError::Refused`](crate::mux::OpenError::Refused), or make room
+    /// by evicting the least recently used lane that has no values buffered,
+    /// as per `at_capacity`.
+    ///
+    /// # Parameters
+    /// * `max` - The maximum number of lanes. Lanes are unlimited by default.
+    /// * `at_capacity` - What to do with new lanes over the limit.
+    #[inline]
+    pub fn with_max_lanes(self, max: usize, at_capacity: AtCapacity) -> Self {
+        self.bus.set_max_lanes(max, at_capacity);
+        self
+    }
 
-            for msg_no in 0..msg_cnt {
-                let msg: String = Faker.fake_with_rng(rng);
-                let lane = mux_tx.send(lane_no, (msg_no, msg.clone())).await;
+    /// Limits how fast new lanes can be created, refusing new lanes over the
+    /// rate like [`Mux::with_max_lanes`](crate::mux::Mux::with_max_lanes).
+    ///
+    /// Up to `lanes` lanes can be created in a burst, after which lanes can
+    /// be created at a rate of `lanes` every `per`.
+    ///
+    /// # Parameters
+    /// * `lanes` - The number of lanes that can be created in a burst.
+    /// * `per` - How long the burst takes to replenish. Lane creation is not
+    ///   rate limited by default.
+    #[inline]
+    pub fn with_lane_rate(self, lanes: u32, per: Duration) -> Self {
+        self.bus.set_lane_rate(lanes, per);
+        self
+    }
 
-                if msg_no == 0 {
-                    tokio::spawn(handle_lane(lane.unwrap(), msg_cnt));
-                }
-            }
-        }
+    /// Gets how often new lanes were refused, or old lanes were evicted, by
+    /// lane admission control so far.
+    #[inline]
+    pub fn admission_stats(&self) -> AdmissionStats {
+        self.bus.admission_stats()
+    }
 
-        async fn handle_lane(lane: Lane<u64, (u64, String)>, msg_cnt: u64) {
-            let (mut tx, mut rx) = lane.split();
-            let rng = &mut get_rng(*tx.tag());
+    /// Gets a snapshot of the statistics of the mux, and of each of its open
+    /// lanes.
+    ///
+    /// See [`LaneRx::stats`](crate::lane::LaneRx::stats) for the statistics
+    /// of a single lane.
+    #[inline]
+    pub fn stats(&self) -> MuxStats<T> {
+        self.bus.stats()
+    }
 
-            for msg_no in 0..msg_cnt {
-                let (actual_msg_no, actual_msg) = rx.recv().await.unwrap();
-                let expected_msg: String = Faker.fake_with_rng(rng);
+    /// Sends a copy of a message to every open lane, as per each lane's
+    /// overflow policy.
+    ///
+    /// See [`Bus::broadcast`](crate::bus::Bus::broadcast).
+    ///
+    /// # Parameters
+    /// * `value` - The message to send.
+    ///
+    /// # Returns
+    /// The outcome of sending the message to each lane.
+    #[inline]
+    pub async fn broadcast(&self, value: V) -> DeliveryReport<T, V>
+    where
+        V: Clone,
+    {
+        self.bus.broadcast(value).await
+    }
 
-                assert_eq!(msg_no, actual_msg_no);
-                assert_eq!(expected_msg, actual_msg);
+    /// Sends a copy of a message to the open lanes with the given tags, as
+    /// per each lane's overflow policy.
+    ///
+    /// See [`Bus::multicast`](crate::bus::Bus::multicast).
+    ///
+    /// # Parameters
+    /// * `tags` - The tags of the lanes.
+    /// * `value` - The message to send.
+    ///
+    /// # Returns
+    /// The outcome of sending the message to each lane.
+    #[inline]
+    pub async fn multicast<I>(&self, tags: I, value: V) -> DeliveryReport<T, V>
+    where
+        I: IntoIterator<Item = T>,
+        V: Clone,
+    {
+        self.bus.multicast(tags, value).await
+    }
 
-                tx.send((msg_no, expected_msg)).await.unwrap();
-            }
+    /// Sends a copy of a message to the open lanes whose tags match a
+    /// predicate, as per each lane's overflow policy.
+    ///
+    /// See [`Bus::multicast_where`](crate::bus::Bus::multicast_where).
+    ///
+    /// # Parameters
+    /// * `predicate` - Whether to send to the lane with the given tag.
+    /// * `value` - The message to send.
+    ///
+    /// # Returns
+    /// The outcome of sending the message to each lane.
+    #[inline]
+    pub async fn multicast_where<F>(
+        &self,
+        predicate: F,
+        value: V,
+    ) -> DeliveryReport<T, V>
+    where
+        F: FnMut(&T) -> bool,
+        V: Clone,
+    {
+        self.bus.multicast_where(predicate, value).await
+    }
 
-            assert_eq!(None, rx.recv().await);
-        }
+    /// Gets a new sender for the open lane with the given tag, which sends
+    /// outgoing messages just like the sender handed out with the lane.
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    ///
+    /// # Returns
+    /// * [`Some(tx)`] - The new sender.
+    /// * [`None`] - If no lane with the given tag is open, or if all of its
+    ///   senders were dropped.
+    #[inline]
+    pub fn sender(&self, tag: &T) -> Option<LaneTx<T, V>> {
+        self.bus.sender(tag)
+    }
 
-        mux_tx.close();
-        pull.await.unwrap();
+    /// Gets a route to the open lane with the given tag, through which
+    /// messages can be pushed to the lane's receiver without looking the lane
+    /// up again.
+    ///
+    /// See [`Bus::route_to`](crate::bus::Bus::route_to).
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    #[inline]
+    pub fn route_to(&self, tag: &T) -> Option<Route<T, V>> {
+        self.bus.route_to(tag)
     }
 
+    /// Subscribes to the lifecycle events of the lanes of the mux, i.e. when
+    /// lanes are opened, half-closed, closed, evicted or overflowed.
+    ///
+    /// This can be called any number of times, where each receiver sees every
+    /// event emitted after it was created, independently of the others.
     #[inline]
-    fn get_rng(lane: u64) -> StdRng {
-        union Seed {
-            u8: [u8; 32],
-            u64: [u64; 4],
+    pub fn events(&self) -> LaneEvents<T> {
+        self.bus.events()
+    }
+
+    /// Sets how many lane events are buffered for each receiver returned by
+    /// [`Mux::events`](crate::mux::Mux::events), past which slow receivers
+    /// miss the oldest ones.
+    ///
+    /// # Parameters
+    /// * `buf` - The buffer size, [`DEFAULT_EVENTS_BUF`] by default.
+    ///
+    /// [`DEFAULT_EVENTS_BUF`]: crate::event::DEFAULT_EVENTS_BUF
+    #[inline]
+    pub fn with_events_buf(mut self, buf: usize) -> Self {
+        self.bus.set_events_buf(buf);
+        self
+    }
+
+    /// Subscribes to the lane with the given tag, opening it as a fan-out lane
+    /// if it is not open yet, so that every subscriber receives every value
+    /// sent to the lane.
+    ///
+    /// This can be called any number of times, where each subscriber receives
+    /// the values sent to the lane after it subscribed, independently of the
+    /// others. The lane is closed once all of its subscribers are dropped.
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    ///
+    /// # Returns
+    /// * [`Ok(rx)`] - The new subscriber.
+    /// * [`Err(OpenError::InUse(tag))`] - If a lane with the given tag is
+    ///   already open, but not as a fan-out lane.
+    /// * [`Err(OpenError::Refused(tag))`] - If the lane was refused by the lane
+    ///   limit or creation rate.
+    pub fn subscribe(&self, tag: T) -> Result<FanoutRx<T, V>, OpenError<T>>
+    where
+        V: Clone,
+    {
+        let mut entry = self.fanouts.entry(tag.clone()).or_default();
+        // kept alive until the entry is released, as dropping the last
+        // reference to a fan-out lane unregisters it
+        let existing = entry.upgrade();
+
+        if let Some(rx) = existing.as_ref().and_then(Fanout::subscribe) {
+            return Ok(rx);
         }
 
-        let seed = Seed {
-            u64: [lane, lane, lane, lane],
+        let fanout = match self.bus.open(tag) {
+            | Ok(rx) => {
+                Arc::new(Fanout::new(rx, self.fanout_buf, self.fanouts.clone()))
+            }
+            | Err(err) => {
+                drop(entry);
+                return Err(err);
+            }
         };
 
-        StdRng::from_seed(unsafe { seed.u8 })
+        *entry = Arc::downgrade(&fanout);
+        drop(entry);
+
+        Ok(fanout.subscribe().expect("a new fan-out lane to be open"))
+    }
+
+    /// Sets how many values are buffered for each subscriber of a fan-out
+    /// lane (see [`Mux::subscribe`](crate::mux::Mux::subscribe)), past which
+    /// slow subscribers miss the oldest ones.
+    ///
+    /// # Parameters
+    /// * `buf` - The buffer size, [`DEFAULT_FANOUT_BUF`] by default.
+    ///
+    /// [`DEFAULT_FANOUT_BUF`]: crate::fanout::DEFAULT_FANOUT_BUF
+    #[inline]
+    pub fn with_fanout_buf(mut self, buf: usize) -> Self {
+        self.fanout_buf = buf;
+        self
+    }
+
+    /// Sets how many times sending retries after hitting a lane that was
+    /// closed meanwhile, before failing with
+    /// [`MuxError::Exhausted`](crate::mux::MuxError::Exhausted).
+    ///
+    /// # Parameters
+    /// * `retries` - The retry budget, [`DEFAULT_RETRIES`] by default.
+    ///
+    /// [`DEFAULT_RETRIES`]: crate::bus::DEFAULT_RETRIES
+    #[inline]
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.bus.set_retries(retries);
+        self
+    }
+
+    /// Sets the allocator of tags for lanes opened with
+    /// [`Mux::open`](crate::mux::Mux::open).
+    ///
+    /// # Parameters
+    /// * `allocator` - The tag allocator, e.g. [`Parity`](crate::tag::Parity).
+    #[inline]
+    pub fn with_allocator<A>(self, allocator: A) -> Self
+    where
+        A: TagAllocator<T> + 'static,
+    {
+        self.set_allocator(allocator);
+        self
+    }
+
+    /// Opens a new lane with a freshly allocated tag.
+    ///
+    /// Tags that are already in use are skipped.
+    ///
+    /// # Returns
+    /// * [`Ok(lane)`] - The new lane.
+    /// * [`Err(OpenError::Exhausted)`] - If no tag could be allocated.
+    /// * [`Err(OpenError::Refused(tag))`] - If the lane was refused by the lane
+    ///   limit or creation rate.
+    pub fn open(&self) -> Result<Lane<T, V>, OpenError<T>> {
+        let mut allocator = self
+            .allocator
+            .0
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        let allocator = allocator.as_mut().ok_or(OpenError::Exhausted)?;
+
+        loop {
+            let tag = allocator.allocate().ok_or(OpenError::Exhausted)?;
+
+            match self.bus.open(tag) {
+                | Ok(rx) => return Ok(self.lane(rx)),
+                | Err(OpenError::InUse(_)) => continue,
+                | Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Opens a new lane with the given tag.
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    ///
+    /// # Returns
+    /// * [`Ok(lane)`] - The new lane.
+    /// * [`Err(OpenError::InUse(tag))`] - If a lane with the given tag is
+    ///   already open.
+    /// * [`Err(OpenError::Refused(tag))`] - If the lane was refused by the lane
+    ///   limit or creation rate.
+    #[inline]
+    pub fn open_with(&self, tag: T) -> Result<Lane<T, V>, OpenError<T>> {
+        self.bus.open(tag).map(|rx| self.lane(rx))
+    }
+
+    /// Opens a new lane with the given tag, whose values are shared among the
+    /// workers of a [`ConsumerGroup`](crate::group::ConsumerGroup).
+    ///
+    /// # Parameters
+    /// * `tag` - The tag of the lane.
+    /// * `assignment` - How values are assigned to the workers.
+    ///
+    /// # Returns
+    /// * [`Ok(group)`] - The group, with no workers yet.
+    /// * [`Err(OpenError::InUse(tag))`] - If a lane with the given tag is
+    ///   already open.
+    /// * [`Err(OpenError::Refused(tag))`] - If the lane was refused by the lane
+    ///   limit or creation rate.
+    #[inline]
+    pub fn group(
+        &self,
+        tag: T,
+        assignment: Assignment,
+    ) -> Result<ConsumerGroup<T, V>, OpenError<T>> {
+        self.bus
+            .open(tag)
+            .map(|rx| ConsumerGroup::new(rx, assignment))
+    }
+
+    /// Delivers new lanes to the returned
+    /// [`Incoming`](crate::incoming::Incoming) rather than returning them
+    /// from [`Mux::send`](crate::mux::Mux::send), so that lanes can be
+    /// handled separately from pushing messages.
+    ///
+    /// Calling this again delivers new lanes to the latest receiver only.
+    ///
+    /// # Parameters
+    /// * `backlog` - The number of new lanes that can be waiting to be accepted
+    ///   before sending to new lanes waits.
+    #[inline]
+    pub fn incoming(&mut self, backlog: usize) -> Incoming<T, V> {
+        let (tx, rx) = mpsc::channel(backlog.max(1));
+
+        self.incoming = Some(tx);
+
+        Incoming::new(rx)
+    }
+
+    /// Close all lanes.
+    #[inline]
+    pub fn close(self) {
+        drop(self)
+    }
+
+    /// Gracefully shuts the mux down.
+    ///
+    /// No new lanes are created, and existing lanes are closed, so that their
+    /// receivers end once they drain the values buffered in them. Their
+    /// senders can still send until they are dropped. This then waits for all
+    /// lane handles to be dropped, at which point the aggregated receiver ends
+    /// once drained.
+    ///
+    /// # Parameters
+    /// * `timeout` - How long to wait for lane handles to be dropped, before
+    ///   aborting them (see [`Mux::abort`](crate::mux::Mux::abort)).
+    ///
+    /// # Returns
+    /// Whether all lane handles were dropped before the timeout elapsed.
+    pub async fn shutdown(self, timeout: Duration) -> bool {
+        let Self { bus, outlets, .. } = self;
+
+        bus.clear();
+        drop(outlets);
+
+        let finished =
+            tokio::time::timeout(timeout, bus.finished()).await.is_ok();
+
+        if !finished {
+            bus.abort();
+        }
+
+        finished
+    }
+
+    /// Shuts the mux down immediately.
+    ///
+    /// All lanes are closed, any values buffered in their receivers are
+    /// discarded, and sending through any of their senders fails. The
+    /// aggregated receiver ends once the remaining lane handles are dropped.
+    #[inline]
+    pub fn abort(self) {
+        self.bus.abort();
+    }
+
+    /// Gets a reference to the underlying [`Bus`](crate::bus::Bus).
+    #[cfg(any(test, feature = "transport"))]
+    #[inline]
+    pub(crate) const fn bus(&self) -> &Bus<T, V> {
+        &self.bus
+    }
+
+    /// Sends a message to a lane, without requiring exclusive access to the
+    /// mux.
+    ///
+    /// See [`Mux::send`](crate::mux::Mux::send).
+    #[inline]
+    pub(crate) async fn deliver(
+        &self,
+        tag: T,
+        value: V,
+    ) -> Result<Delivery<Lane<T, V>>, MuxError<T, V>> {
+        let rx = match self.bus.push(tag, value).await?.into_lane() {
+            | Ok(rx) => rx,
+            | Err(delivery) => return Ok(delivery),
+        };
+
+        Ok(match self.offer(self.lane(rx)).await {
+            | Some(lane) => Delivery::New(lane),
+            | None => Delivery::Accepted,
+        })
+    }
+
+    /// Offers a new lane to [`Incoming`](crate::incoming::Incoming), if any.
+    ///
+    /// # Returns
+    /// * [`None`] - If the lane was delivered.
+    /// * [`Some(lane)`] - If nobody is accepting lanes.
+    #[inline]
+    pub(crate) async fn offer(&self, lane: Lane<T, V>) -> Option<Lane<T, V>> {
+        match self.incoming {
+            | Some(ref incoming) => {
+                incoming.send(lane).await.err().map(|err| err.0)
+            }
+            | None => Some(lane),
+        }
+    }
+
+    /// Attempts to send a message to a lane without waiting, and without
+    /// requiring exclusive access to the mux.
+    ///
+    /// See [`Mux::try_send`](crate::mux::Mux::try_send).
+    #[inline]
+    pub(crate) fn try_deliver(
+        &self,
+        tag: T,
+        value: V,
+    ) -> Result<Delivery<Lane<T, V>>, MuxError<T, V>> {
+        let rx = match self.bus.try_push(tag, value)?.into_lane() {
+            | Ok(rx) => rx,
+            | Err(delivery) => return Ok(delivery),
+        };
+
+        Ok(match self.try_offer(self.lane(rx)) {
+            | Some(lane) => Delivery::New(lane),
+            | None => Delivery::Accepted,
+        })
+    }
+
+    /// Offers a new lane to [`Incoming`](crate::incoming::Incoming), if any,
+    /// without waiting for room in its backlog.
+    ///
+    /// # Returns
+    /// * [`None`] - If the lane was delivered.
+    /// * [`Some(lane)`] - If nobody is accepting lanes, or the backlog is full.
+    #[inline]
+    pub(crate) fn try_offer(&self, lane: Lane<T, V>) -> Option<Lane<T, V>> {
+        match self.incoming {
+            | Some(ref incoming) => match incoming.try_send(lane) {
+                | Ok(()) => None,
+                | Err(
+                    TrySendError::Full(lane) | TrySendError::Closed(lane),
+                ) => Some(lane),
+            },
+            | None => Some(lane),
+        }
+    }
+
+    /// Sets the allocator of tags for lanes opened with
+    /// [`Mux::open`](crate::mux::Mux::open), without requiring ownership of
+    /// the mux.
+    #[inline]
+    pub(crate) fn set_allocator<A>(&self, allocator: A)
+    where
+        A: TagAllocator<T> + 'static,
+    {
+        let mut slot = self
+            .allocator
+            .0
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+
+        *slot = Some(Box::new(allocator));
+    }
+
+    /// Waits until nobody is accepting lanes through
+    /// [`Incoming`](crate::incoming::Incoming) anymore.
+    #[cfg(feature = "transport")]
+    #[inline]
+    pub(crate) async fn refusing(&self) {
+        match self.incoming {
+            | Some(ref incoming) => incoming.closed().await,
+            | None => {}
+        }
+    }
+
+    #[inline]
+    fn lane(&self, rx: LaneRx<T, V>) -> Lane<T, V> {
+        let tag = rx.tag().clone();
+        let weight = self.weights.get(&tag).map_or(self.weight, |w| *w);
+        let (tx, weight) = self.outlets.open(weight);
+        let meter = rx.meter().clone();
+        let lifecycle = rx.lifecycle().clone();
+
+        // a shared queue is not the lane's own to report
+        if weight.is_some() {
+            meter.attach(tx.downgrade());
+        }
+
+        let tx = match self.hooks {
+            | Some(ref hooks) => LaneTx::guarded(tx, tag, hooks, meter),
+            | None => LaneTx::new(tx, tag, meter),
+        };
+
+        let tx = tx.tracked(self.bus.track()).observed(lifecycle);
+        let tx = match weight {
+            | Some(weight) => tx.weighted(weight),
+            | None => tx,
+        };
+
+        _ = rx.senders().set(tx.downgrade());
+
+        Lane::from_parts(tx, rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::stats::Depth;
+    use crate::{CloseReason, LaneEvent};
+
+    #[tokio::test]
+    async fn mux_test() {
+        let buf: usize = (1..32).fake();
+        let lane_buf: usize = (1..8).fake();
+        let lane_cnt: u64 = 1000;
+        let msg_cnt: u64 = 1000;
+
+        let (mut mux_tx, mut mux_rx) = Mux::new(buf, lane_buf);
+
+        let pull = tokio::spawn(async move {
+            for lane_no in 0..lane_cnt {
+                let rng = &mut get_rng(lane_no);
+
+                for msg_no in 0..msg_cnt {
+                    let (actual_tag, actual_msg) = mux_rx.recv().await.unwrap();
+
+                    assert_eq!(lane_no, actual_tag);
+
+                    let (actual_msg_no, actual_msg): (u64, String) = actual_msg;
+                    let expected_msg: String = Faker.fake_with_rng(rng);
+
+                    assert_eq!(msg_no, actual_msg_no);
+                    assert_eq!(expected_msg, actual_msg);
+                }
+            }
+
+            assert_eq!(None, mux_rx.recv().await);
+        });
+
+        for lane_no in 0..lane_cnt {
+            let rng = &mut get_rng(lane_no);
+
+            for msg_no in 0..msg_cnt {
+                let msg: String = Faker.fake_with_rng(rng);
+                let lane = mux_tx.send(lane_no, (msg_no, msg.clone())).await;
+
+                if msg_no == 0 {
+                    let lane = lane.unwrap().lane().unwrap();
+
+                    tokio::spawn(handle_lane(lane, msg_cnt));
+                }
+            }
+        }
+
+        async fn handle_lane(lane: Lane<u64, (u64, String)>, msg_cnt: u64) {
+            let (mut tx, mut rx) = lane.split();
+            let rng = &mut get_rng(*tx.tag());
+
+            for msg_no in 0..msg_cnt {
+                let (actual_msg_no, actual_msg) = rx.recv().await.unwrap();
+                let expected_msg: String = Faker.fake_with_rng(rng);
+
+                assert_eq!(msg_no, actual_msg_no);
+                assert_eq!(expected_msg, actual_msg);
+
+                tx.send((msg_no, expected_msg)).await.unwrap();
+            }
+
+            assert_eq!(None, rx.recv().await);
+        }
+
+        mux_tx.close();
+        pull.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn mux_incoming_test() {
+        let (mut mux, mut mux_rx) = Mux::new(8, 8);
+        let mut incoming = mux.incoming(2);
+
+        for tag in 0..2 {
+            assert!(matches!(
+                mux.send(tag, tag * 10).await,
+                Ok(Delivery::Accepted)
+            ));
+            assert!(matches!(
+                mux.send(tag, tag * 10 + 1).await,
+                Ok(Delivery::Existing)
+            ));
+        }
+
+        for tag in 0..2 {
+            let (mut tx, mut rx) = incoming.accept().await.unwrap().split();
+
+            assert_eq!(&tag, tx.tag());
+            assert_eq!(Some(tag * 10), rx.recv().await);
+            assert_eq!(Some(tag * 10 + 1), rx.recv().await);
+
+            tx.send(tag).await.unwrap();
+            assert_eq!(Some((tag, tag)), mux_rx.recv().await);
+        }
+
+        // new lanes are returned once nobody is accepting them
+        drop(incoming);
+
+        let lane = mux.send(2, 20).await.unwrap().lane().unwrap();
+
+        assert_eq!(&2, lane.split().0.tag());
+    }
+
+    #[tokio::test]
+    async fn mux_open_test() {
+        let (mux, mut mux_rx) = Mux::new(8, 8);
+
+        assert_eq!(Err(OpenError::Exhausted), mux.open().map(|_| ()));
+
+        let mux = mux.with_allocator(crate::Parity::<u8>::odd());
+        let (mut tx, mut rx) = mux.open().unwrap().split();
+
+        assert_eq!(&1, tx.tag());
+        assert_eq!(Err(OpenError::InUse(1)), mux.open_with(1).map(|_| ()));
+
+        tx.send("out").await.unwrap();
+        assert_eq!(Some((1, "out")), mux_rx.recv().await);
+
+        assert!(matches!(mux.deliver(1, "in").await, Ok(Delivery::Existing)));
+        assert_eq!(Some("in"), rx.recv().await);
+
+        // tags in use are skipped
+        let lane = mux.open_with(3).unwrap();
+
+        assert_eq!(&5, mux.open().unwrap().split().0.tag());
+
+        drop(lane);
+    }
+
+    #[tokio::test]
+    async fn mux_shutdown_test() {
+        let timeout = Duration::from_secs(5);
+        let (mut mux, mut mux_rx) = Mux::new(8, 8);
+        let (mut tx, mut rx) =
+            mux.send(1, 1).await.unwrap().lane().unwrap().split();
+
+        assert!(matches!(mux.send(1, 2).await, Ok(Delivery::Existing)));
+
+        let handler = tokio::spawn(async move {
+            // buffered values are drained before the lane ends
+            while let Some(value) = rx.recv().await {
+                tx.send(value * 10).await.unwrap();
+            }
+        });
+
+        assert!(mux.shutdown(timeout).await);
+        assert_eq!(Some((1, 10)), mux_rx.recv().await);
+        assert_eq!(Some((1, 20)), mux_rx.recv().await);
+        assert_eq!(None, mux_rx.recv().await);
+
+        handler.await.unwrap();
+
+        // lanes that are not dropped in time are aborted
+        let (mut mux, mut mux_rx) = Mux::new(8, 8);
+        let (mut tx, mut rx) =
+            mux.send(1, 1).await.unwrap().lane().unwrap().split();
+
+        assert!(!mux.shutdown(Duration::from_millis(10)).await);
+        assert_eq!(None, rx.recv().await);
+        assert!(tx.is_closed());
+        assert!(tx.send(1).await.is_err());
+
+        drop((tx, rx));
+        assert_eq!(None, mux_rx.recv().await);
+    }
+
+    #[tokio::test]
+    async fn mux_abort_test() {
+        let (mut mux, _mux_rx) = Mux::new(8, 8);
+        let (mut tx, mut rx) =
+            mux.send(1, 1).await.unwrap().lane().unwrap().split();
+
+        assert!(matches!(mux.send(1, 2).await, Ok(Delivery::Existing)));
+
+        mux.abort();
+
+        // buffered values are discarded
+        assert_eq!(None, rx.recv().await);
+        assert!(tx.send(1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn mux_close_reason_test() {
+        let (mut mux, _mux_rx) = Mux::new(8, 8);
+        let mut lanes = Vec::new();
+
+        for tag in 0..3 {
+            lanes.push(
+                mux.send(tag, tag).await.unwrap().lane().unwrap().split().1,
+            );
+        }
+
+        mux.bus().close(&0);
+        lanes[1].close();
+        drop(mux);
+
+        let [graceful, local, shutdown] = &mut lanes[..] else {
+            unreachable!()
+        };
+
+        // values sent before closing are still received
+        assert!(matches!(graceful.recv_result().await, Ok(0)));
+        assert!(matches!(
+            graceful.recv_result().await,
+            Err(CloseReason::Graceful)
+        ));
+        assert!(matches!(
+            local.recv_result().await,
+            Err(CloseReason::LocalClose)
+        ));
+        assert!(matches!(shutdown.recv_result().await, Ok(2)));
+        assert!(matches!(
+            shutdown.recv_result().await,
+            Err(CloseReason::Shutdown)
+        ));
+    }
+
+    #[tokio::test]
+    async fn mux_send_error_test() {
+        let (mux, _mux_rx) = Mux::new(8, 8);
+        let mut mux = mux.with_retries(0);
+        let (_tx, mut rx) =
+            mux.send(1, 1).await.unwrap().lane().unwrap().split();
+
+        rx.close();
+
+        // a closed lane is replaced by a new one
+        assert!(matches!(mux.send(1, 2).await, Ok(Delivery::New(_))));
+
+        mux.bus().abort();
+
+        let err = mux.send(2, 4).await.unwrap_err();
+
+        assert_eq!((2, 4), err.clone().into_inner());
+        assert_eq!(MuxError::Shutdown(2, 4), err);
+        assert_eq!(
+            "mux was shut down before delivering to lane 2",
+            err.to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn mux_try_send_test() {
+        let timeout = Duration::from_millis(10);
+        let (mut mux, mut mux_rx) = Mux::new(1, 1);
+        let lane = mux.try_send(1, 1).unwrap().lane().unwrap();
+        let (mut tx, mut rx) = lane.split();
+
+        assert_eq!(Err(MuxError::Full(1, 2)), mux.try_send(1, 2).map(|_| ()));
+        assert_eq!(
+            Err(MuxError::Timeout(1, 3)),
+            mux.send_timeout(1, 3, timeout).await.map(|_| ())
+        );
+        assert_eq!(Some(1), rx.recv().await);
+        assert!(matches!(mux.try_send(1, 4), Ok(Delivery::Existing)));
+        assert!(matches!(
+            mux.send_timeout(2, 5, timeout).await,
+            Ok(Delivery::New(_))
+        ));
+
+        tx.try_send(10).unwrap();
+        assert!(matches!(tx.try_send(20), Err(TrySendError::Full((1, 20)))));
+        assert!(matches!(
+            tx.send_timeout(30, timeout).await,
+            Err(SendTimeoutError::Timeout((1, 30)))
+        ));
+        assert_eq!(Some((1, 10)), mux_rx.recv().await);
+        tx.send_timeout(40, timeout).await.unwrap();
+        assert_eq!(Some((1, 40)), mux_rx.recv().await);
+    }
+
+    #[tokio::test]
+    async fn mux_overflow_test() {
+        let (mux, _mux_rx) = Mux::new(8, 1);
+        let mut mux = mux.with_overflow(Overflow::DropNewest);
+
+        mux.set_lane_overflow(2, Overflow::DropOldest);
+        mux.set_lane_overflow(3, Overflow::Reject);
+        mux.set_lane_overflow(4, Overflow::Unbounded);
+
+        let mut lanes = Vec::new();
+
+        for tag in 1..=4 {
+            lanes.push(mux.send(tag, 0).await.unwrap().lane().unwrap());
+        }
+
+        assert!(matches!(mux.send(1, 1).await, Ok(Delivery::Dropped)));
+        assert!(matches!(mux.send(2, 1).await, Ok(Delivery::Existing)));
+        assert_eq!(Err(MuxError::Full(3, 1)), mux.send(3, 1).await.map(|_| ()));
+
+        for value in 1..10_000 {
+            assert!(matches!(mux.try_send(4, value), Ok(Delivery::Existing)));
+        }
+
+        let [drop_newest, drop_oldest, reject, unbounded] = &mut lanes[..]
+        else {
+            unreachable!()
+        };
+
+        assert_eq!(Some(0), drop_newest.receiver().recv().await);
+        assert_eq!(Some(1), drop_oldest.receiver().recv().await);
+        assert_eq!(Some(0), reject.receiver().recv().await);
+
+        assert_eq!(
+            Depth {
+                len: 10_000,
+                capacity: None
+            },
+            unbounded.receiver().stats().inbound
+        );
+
+        for value in 0..10_000 {
+            assert_eq!(Some(value), unbounded.receiver().recv().await);
+        }
+    }
+
+    #[tokio::test]
+    async fn mux_park_test() {
+        let (mux, _mux_rx) = Mux::new(8, 1);
+        let mut mux = mux.with_overflow(Overflow::Park).with_park_budget(2);
+        let mut congested = mux.send(1, 0).await.unwrap().lane().unwrap();
+
+        for value in 1..=2 {
+            assert!(matches!(mux.send(1, value).await, Ok(Delivery::Existing)));
+        }
+
+        // other lanes are not held up by the congested one
+        let mut other = mux.send(2, 0).await.unwrap().lane().unwrap();
+
+        assert_eq!(Some(0), other.receiver().recv().await);
+
+        // the park budget ran out
+        let timeout = Duration::from_millis(10);
+
+        assert_eq!(Err(MuxError::Full(1, 3)), mux.try_send(1, 3).map(|_| ()));
+        assert_eq!(
+            Err(MuxError::Timeout(1, 3)),
+            mux.send_timeout(1, 3, timeout).await.map(|_| ())
+        );
+
+        // parked values are received in order as the lane drains
+        assert_eq!(Some(0), congested.receiver().recv().await);
+        assert_eq!(Some(1), congested.receiver().recv().await);
+        assert!(matches!(mux.try_send(1, 3), Ok(Delivery::Existing)));
+
+        for value in 2..=3 {
+            assert_eq!(Some(value), congested.receiver().recv().await);
+        }
+
+        // parked values are discarded along with the lane, freeing the budget
+        for value in 4..=6 {
+            assert!(matches!(mux.send(1, value).await, Ok(Delivery::Existing)));
+        }
+
+        drop(congested);
+
+        for value in 0..=2 {
+            assert!(mux.try_send(3, value).is_ok());
+        }
+
+        // resizing the budget counts the values parked so far
+        let (mux, _mux_rx) = Mux::new(8, 1);
+        let mut mux = mux.with_overflow(Overflow::Park).with_park_budget(2);
+        let mut lane = mux.send(1, 0).await.unwrap().lane().unwrap();
+
+        for value in 1..=2 {
+            assert!(matches!(mux.send(1, value).await, Ok(Delivery::Existing)));
+        }
+
+        mux.bus().set_park_budget(1);
+
+        for value in 0..=1 {
+            assert_eq!(Some(value), lane.receiver().recv().await);
+            assert_eq!(
+                Err(MuxError::Full(1, 3)),
+                mux.try_send(1, 3).map(|_| ())
+            );
+        }
+
+        assert_eq!(Some(2), lane.receiver().recv().await);
+        assert!(matches!(mux.try_send(1, 3), Ok(Delivery::Existing)));
+        assert!(matches!(mux.try_send(1, 4), Ok(Delivery::Existing)));
+        assert_eq!(Err(MuxError::Full(1, 5)), mux.try_send(1, 5).map(|_| ()));
+
+        mux.bus().set_park_budget(3);
+        assert!(matches!(mux.try_send(1, 5), Ok(Delivery::Existing)));
+        assert!(matches!(mux.try_send(1, 6), Ok(Delivery::Existing)));
+    }
+
+    #[tokio::test]
+    async fn mux_weight_test() {
+        let (mux, mut mux_rx) = Mux::scheduled(16, 8);
+        let mut mux = mux.with_weight(2);
+
+        mux.set_lane_weight(3, 1);
+
+        let mut lanes = Vec::new();
+
+        for tag in 1..=3 {
+            lanes.push(mux.send(tag, 0).await.unwrap().lane().unwrap());
+        }
+
+        // a chatty lane queues up first
+        for value in 0..6 {
+            lanes[0].sender().send(value).await.unwrap();
+        }
+
+        lanes[1].set_weight(3);
+
+        for lane in &mut lanes[1..] {
+            for value in 0..3 {
+                lane.sender().send(value).await.unwrap();
+            }
+        }
+
+        let mut order = Vec::new();
+
+        for _ in 0..12 {
+            order.push(mux_rx.recv().await.unwrap().0);
+        }
+
+        assert_eq!(vec![1, 1, 2, 2, 2, 3, 1, 1, 3, 1, 1, 3], order);
+    }
+
+    #[tokio::test]
+    async fn mux_idle_test() {
+        let timeout = Duration::from_millis(100);
+        let (mux, _mux_rx) = Mux::new(8, 8);
+        let mut mux = mux.with_idle_timeout(timeout);
+
+        mux.set_lane_idle_timeout(2, Duration::from_secs(60));
+
+        // a waiting receiver is closed once the lane becomes idle
+        let (_tx, mut waiting) =
+            mux.send(1, 1).await.unwrap().lane().unwrap().split();
+
+        assert_eq!(Some(1), waiting.recv().await);
+        assert!(matches!(
+            waiting.recv_result().await,
+            Err(CloseReason::Idle)
+        ));
+
+        // unattended lanes are evicted along with their buffered values
+        let (_tx, mut unattended) =
+            mux.send(3, 1).await.unwrap().lane().unwrap().split();
+        let _kept = mux.send(2, 1).await.unwrap().lane().unwrap();
+        let (mut tx, _busy) =
+            mux.send(4, 1).await.unwrap().lane().unwrap().split();
+
+        assert_eq!(0, mux.evict_idle());
+
+        // outgoing traffic keeps a lane alive as well
+        for value in 0..4 {
+            tokio::time::sleep(timeout / 2).await;
+            tx.send(value).await.unwrap();
+        }
+
+        assert_eq!(1, mux.evict_idle());
+        assert!(matches!(
+            unattended.recv_result().await,
+            Err(CloseReason::Idle)
+        ));
+    }
+
+    #[tokio::test]
+    async fn mux_idle_sweep_test() {
+        let timeout = Duration::from_millis(100);
+        let (mux, _mux_rx) = Mux::new(8, 8);
+        let mut mux = mux.with_idle_timeout(timeout);
+
+        // a forgotten lane is reclaimed by the traffic of other lanes
+        let mut forgotten = mux.send(1, 1).await.unwrap().lane().unwrap();
+        let _busy = mux.send(2, 1).await.unwrap().lane().unwrap();
+
+        tokio::time::sleep(timeout * 3 / 5).await;
+        mux.send(2, 2).await.unwrap();
+
+        assert_eq!(2, mux.stats().lanes.len());
+
+        tokio::time::sleep(timeout * 3 / 5).await;
+        mux.send(2, 3).await.unwrap();
+
+        let stats = mux.stats();
+
+        assert_eq!(1, stats.lanes.len());
+        assert_eq!(2, stats.lanes[0].0);
+        assert!(matches!(
+            forgotten.receiver().recv_result().await,
+            Err(CloseReason::Idle)
+        ));
+    }
+
+    #[tokio::test]
+    async fn mux_admission_test() {
+        // new lanes over the limit are refused until a lane is closed
+        let (mux, _mux_rx) = Mux::new(8, 8);
+        let mut mux = mux.with_max_lanes(2, AtCapacity::Reject);
+
+        let _first = mux.send(1, 1).await.unwrap().lane().unwrap();
+        let second = mux.send(2, 1).await.unwrap().lane().unwrap();
+
+        assert_eq!(
+            Err(MuxError::Refused(3, 1)),
+            mux.send(3, 1).await.map(|_| ())
+        );
+        assert_eq!(Err(OpenError::Refused(3)), mux.open_with(3).map(|_| ()));
+        assert!(matches!(mux.send(1, 2).await, Ok(Delivery::Existing)));
+
+        drop(second);
+
+        let _third = mux.send(3, 1).await.unwrap().lane().unwrap();
+
+        assert_eq!(2, mux.admission_stats().rejected);
+
+        // or make room by evicting the least recently used idle lane
+        let (mux, _mux_rx) = Mux::new(8, 8);
+        let mut mux = mux.with_max_lanes(2, AtCapacity::EvictLru);
+
+        let (_tx, mut first) =
+            mux.send(1, 1).await.unwrap().lane().unwrap().split();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let (_tx, mut second) =
+            mux.send(2, 1).await.unwrap().lane().unwrap().split();
+
+        assert_eq!(Some(1), second.recv().await);
+
+        let _third = mux.send(3, 1).await.unwrap().lane().unwrap();
+
+        assert!(matches!(
+            second.recv_result().await,
+            Err(CloseReason::Evicted)
+        ));
+
+        // lanes with values buffered are never evicted
+        assert_eq!(
+            Err(MuxError::Refused(4, 1)),
+            mux.send(4, 1).await.map(|_| ())
+        );
+        assert_eq!(Some(1), first.recv().await);
+        assert_eq!(
+            AdmissionStats {
+                rejected: 1,
+                evicted: 1,
+                ..Default::default()
+            },
+            mux.admission_stats()
+        );
+
+        // and lanes can only be created so fast, where refused lanes do not
+        // count
+        let (mux, _mux_rx) = Mux::new(8, 8);
+        let mut mux = mux
+            .with_lane_rate(2, Duration::from_secs(3600))
+            .with_max_lanes(1, AtCapacity::Reject);
+
+        let first = mux.send(1, 1).await.unwrap().lane().unwrap();
+
+        assert_eq!(Err(OpenError::Refused(2)), mux.open_with(2).map(|_| ()));
+        drop(first);
+
+        let _second = mux.open_with(2).unwrap();
+
+        assert_eq!(
+            Err(MuxError::Refused(3, 1)),
+            mux.send(3, 1).await.map(|_| ())
+        );
+        assert_eq!(1, mux.admission_stats().throttled);
+    }
+
+    #[tokio::test]
+    async fn mux_stats_test() {
+        let (mux, mut mux_rx) = Mux::scheduled(8, 2);
+        let mut mux = mux.with_overflow(Overflow::DropNewest);
+
+        let (mut tx, mut rx) =
+            mux.send(1, 1).await.unwrap().lane().unwrap().split();
+
+        for value in 2..=3 {
+            mux.send(1, value).await.unwrap();
+        }
+
+        tx.send(10).await.unwrap();
+
+        let stats = rx.stats();
+
+        assert_eq!(3, stats.totals.received);
+        assert_eq!(1, stats.totals.dropped);
+        assert_eq!(2, stats.inbound.len);
+        assert_eq!(Some(2), stats.inbound.capacity);
+        assert_eq!(1, stats.outbound.len);
+        assert_eq!(Some(8), stats.outbound.capacity);
+        assert!(stats.created <= stats.last_active);
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert_eq!(Some(1), rx.recv().await);
+        assert_eq!(Some(2), rx.recv().await);
+        assert_eq!(Some((1, 10)), mux_rx.recv().await);
+
+        // both halves share the same statistics
+        let stats = tx.stats();
+
+        assert_eq!(2, stats.totals.consumed);
+        assert_eq!(1, stats.totals.sent);
+        assert_eq!(0, stats.inbound.len);
+        assert_eq!(0, stats.outbound.len);
+        assert!(
+            stats.totals.latency.quantile(0.5) > Some(Duration::from_millis(8))
+        );
+
+        // closed lanes still count towards the totals of the mux
+        let _other = mux.send(2, 1).await.unwrap().lane().unwrap();
+
+        drop((tx, rx));
+
+        let stats = mux.stats();
+
+        assert_eq!(1, stats.lanes.len());
+        assert_eq!(2, stats.lanes[0].0);
+        assert_eq!(4, stats.totals.received);
+        assert_eq!(2, stats.totals.latency.count());
+    }
+
+    #[tokio::test]
+    async fn mux_subscribe_test() {
+        let (mux, _mux_rx) = Mux::new(8, 8);
+        let mut mux = mux.with_fanout_buf(2);
+        let mut a = mux.subscribe(1).unwrap();
+        let mut b = mux.subscribe(1).unwrap();
+
+        assert!(matches!(mux.open_with(1), Err(OpenError::InUse(1))));
+
+        for value in 0..2 {
+            mux.send(1, value).await.unwrap();
+        }
+
+        // every subscriber receives every value
+        for rx in [&mut a, &mut b] {
+            assert_eq!(Some(0), rx.recv().await);
+            assert_eq!(Some(1), rx.recv().await);
+        }
+
+        for value in 2..6 {
+            mux.send(1, value).await.unwrap();
+        }
+
+        for value in 2..6 {
+            assert_eq!(Some(value), a.recv().await);
+        }
+
+        // slow subscribers skip the oldest values
+        assert_eq!(Some(4), b.recv().await);
+        assert_eq!((0, 2), (a.missed(), b.missed()));
+
+        // the lane is closed once all subscribers are dropped
+        drop((a, b));
+
+        let mut c = mux.subscribe(1).unwrap();
+
+        mux.send(1, 6).await.unwrap();
+        assert_eq!(Some(6), c.recv().await);
+
+        let _lane = mux.send(2, 7).await.unwrap().lane().unwrap();
+
+        assert!(matches!(mux.subscribe(2), Err(OpenError::InUse(2))));
+
+        drop(mux);
+
+        assert_eq!(None, c.recv().await);
+    }
+
+    #[tokio::test]
+    async fn mux_sender_test() {
+        let (mut mux, mut mux_rx) = Mux::new(8, 8);
+        let (tx, mut rx) =
+            mux.send(1, 1).await.unwrap().lane().unwrap().split();
+
+        assert!(mux.sender(&2).is_none());
+        assert!(mux.route_to(&2).is_none());
+
+        // senders made from the tag alone keep the lane's outgoing side open
+        let mut sender = mux.sender(&1).unwrap();
+        let mut route = mux.route_to(&1).unwrap();
+
+        drop(tx);
+        sender.send(10).await.unwrap();
+        assert_eq!(Some((1, 10)), mux_rx.recv().await);
+        assert_eq!(1, sender.stats().totals.sent);
+
+        assert_eq!(Ok(true), route.push(2).await);
+        assert_eq!(Ok(true), route.try_push(3));
+        for value in 1..=3 {
+            assert_eq!(Some(value), rx.recv().await);
+        }
+
+        // routes are tied to the lane they were made for
+        drop((sender, rx));
+        let _lane = mux.send(1, 4).await.unwrap().lane().unwrap();
+
+        assert!(route.is_closed());
+        assert_eq!(Err(MuxError::Rejected(1, 5)), route.push(5).await);
+        assert!(mux.sender(&1).is_some());
+    }
+
+    #[tokio::test]
+    async fn mux_route_test() {
+        let (mut mux, _mux_rx) = Mux::new(8, 8);
+        let mut rx = mux.send(1, 1).await.unwrap().lane().unwrap().split().1;
+        let mut route = mux.route_to(&1).unwrap();
+
+        // routes fail once the lane is closed, which ends the receiver even
+        // while blocked, after the values already pushed
+        assert_eq!(Ok(true), route.push(2).await);
+        assert_eq!(Some(1), rx.recv().await);
+        assert_eq!(Some(2), rx.recv().await);
+
+        let pending = tokio::spawn(async move { rx.recv_result().await });
+
+        tokio::task::yield_now().await;
+        assert!(mux.bus().close(&1));
+        assert!(route.is_closed());
+        assert_eq!(Err(MuxError::Rejected(1, 3)), route.push(3).await);
+        assert!(matches!(pending.await.unwrap(), Err(CloseReason::Graceful)));
+
+        // the lane can be recreated under the same tag, without the old
+        // receiver removing it once it ends
+        let mut rx = mux.send(2, 1).await.unwrap().lane().unwrap().split().1;
+        let mut route = mux.route_to(&2).unwrap();
+
+        assert!(mux.bus().close(&2));
+        let mut new_rx =
+            mux.send(2, 2).await.unwrap().lane().unwrap().split().1;
+
+        assert_eq!(Err(MuxError::Rejected(2, 3)), route.push(3).await);
+        assert_eq!(Some(1), rx.recv().await);
+        assert_eq!(None, rx.recv().await);
+        drop(rx);
+
+        let mut new_route = mux.route_to(&2).unwrap();
+
+        assert_eq!(Ok(true), new_route.push(3).await);
+        assert_eq!(Some(2), new_rx.recv().await);
+        assert_eq!(Some(3), new_rx.recv().await);
+
+        // routes fail once their lane is evicted too
+        let (mux, _mux_rx) = Mux::new(8, 8);
+        let mut mux = mux.with_max_lanes(1, AtCapacity::EvictLru);
+        let mut rx = mux.send(1, 1).await.unwrap().lane().unwrap().split().1;
+        let mut route = mux.route_to(&1).unwrap();
+
+        assert_eq!(Some(1), rx.recv().await);
+        let _lane = mux.send(2, 1).await.unwrap().lane().unwrap();
+
+        assert!(route.is_closed());
+        assert_eq!(Err(MuxError::Rejected(1, 2)), route.push(2).await);
+        assert!(matches!(rx.recv_result().await, Err(CloseReason::Evicted)));
+    }
+
+    #[tokio::test]
+    async fn mux_multicast_test() {
+        let (mux, _mux_rx) = Mux::new(8, 2);
+        let mut mux = mux.with_overflow(Overflow::Reject);
+
+        mux.set_lane_overflow(2, Overflow::DropNewest);
+
+        let mut lanes: Vec<_> = Vec::new();
+
+        for tag in 1..=3 {
+            lanes.push(mux.send(tag, 0).await.unwrap().lane().unwrap());
+        }
+
+        let mut report = mux.broadcast(10).await;
+
+        report.delivered.sort_unstable();
+        assert_eq!(vec![1, 2, 3], report.delivered);
+        assert!(report.is_complete());
+
+        // lanes 1 and 2 are full by now
+        let report = mux.multicast([1, 2, 4], 20).await;
+
+        assert!(report.delivered.is_empty());
+        assert_eq!(vec![2], report.dropped);
+        assert_eq!(
+            vec![MuxError::Full(1, 20), MuxError::Rejected(4, 20)],
+            report.failed
+        );
+
+        // every lane gets its own copy, in order
+        for lane in &mut lanes {
+            assert_eq!(Some(0), lane.receiver().recv().await);
+            assert_eq!(Some(10), lane.receiver().recv().await);
+        }
+
+        let report = mux.multicast_where(|tag| tag % 2 == 1, 30).await;
+
+        report
+            .delivered
+            .iter()
+            .for_each(|tag| assert!(tag % 2 == 1));
+        assert_eq!(2, report.delivered.len());
+        assert_eq!(Some(30), lanes[2].receiver().recv().await);
+    }
+
+    #[tokio::test]
+    async fn mux_events_test() {
+        let (mux, _mux_rx) = Mux::new(8, 1);
+        let mut mux = mux
+            .with_overflow(Overflow::DropNewest)
+            .with_max_lanes(1, AtCapacity::EvictLru);
+        let mut events = mux.events();
+        let mut other = mux.events();
+
+        let (tx, mut rx) =
+            mux.send(1, 1).await.unwrap().lane().unwrap().split();
+
+        mux.send(1, 2).await.unwrap();
+        drop(tx);
+        assert_eq!(Some(1), rx.recv().await);
+
+        let mut lane = mux.send(2, 1).await.unwrap().lane().unwrap();
+
+        lane.receiver().close();
+        drop((lane, rx, mux));
+
+        let mut seen = Vec::new();
+
+        while let Some(event) = events.recv().await {
+            seen.push(event);
+        }
+
+        assert!(matches!(
+            &seen[..],
+            [
+                LaneEvent::Opened(1),
+                LaneEvent::Overflowed(1),
+                LaneEvent::HalfClosed(1),
+                LaneEvent::Evicted(1),
+                LaneEvent::Opened(2),
+                LaneEvent::Closed {
+                    tag: 2,
+                    reason: CloseReason::LocalClose
+                },
+                LaneEvent::HalfClosed(2),
+            ]
+        ));
+
+        // every receiver sees every event
+        for event in seen {
+            let other = other.recv().await.unwrap();
+
+            assert_eq!(format!("{event:?}"), format!("{other:?}"));
+        }
+
+        assert_eq!(0, events.missed());
+
+        // lanes that already ended are not closed again once dropped
+        let (mut mux, _mux_rx) = Mux::new(8, 1);
+        let mut events = mux.events();
+        let mut lane = mux.send(1, 1).await.unwrap().lane().unwrap();
+
+        drop(mux);
+        assert_eq!(Some(1), lane.receiver().recv().await);
+        assert_eq!(None, lane.receiver().recv().await);
+        drop(lane);
+
+        let mut seen = Vec::new();
+
+        while let Some(event) = events.recv().await {
+            seen.push(event);
+        }
+
+        assert!(matches!(
+            &seen[..],
+            [
+                LaneEvent::Opened(1),
+                LaneEvent::Closed {
+                    tag: 1,
+                    reason: CloseReason::Shutdown
+                },
+                LaneEvent::HalfClosed(1),
+            ]
+        ));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn mux_tracing_test() {
+        let recorder = Recorder::default();
+        let log = recorder.log.clone();
+        let _guard = tracing::subscriber::set_default(recorder);
+
+        let (mux, _mux_rx) = Mux::new(8, 1);
+        let mut mux = mux.with_overflow(Overflow::DropNewest);
+        let mut lane = mux.send(1, 1).await.unwrap().lane().unwrap();
+
+        mux.send(1, 2).await.unwrap();
+        assert_eq!(Some(1), lane.receiver().recv().await);
+        mux.close();
+
+        // events are emitted within the span of their lane, which receiving
+        // enters as well
+        let log = log.lock().unwrap();
+        let lane_log: Vec<_> = log
+            .iter()
+            .filter(|(span, _)| span == "1")
+            .map(|(_, entry)| entry.as_str())
+            .collect();
+
+        assert_eq!(
+            vec![
+                "lane created",
+                "lane is full, dropped the newest value",
+                "enter",
+                "lane closed",
+            ],
+            lane_log
+        );
+    }
+
+    #[inline]
+    fn get_rng(lane: u64) -> StdRng {
+        union Seed {
+            u8: [u8; 32],
+            u64: [u64; 4],
+        }
+
+        let seed = Seed {
+            u64: [lane, lane, lane, lane],
+        };
+
+        StdRng::from_seed(unsafe { seed.u8 })
+    }
+
+    /// A subscriber that records the events and span entries of each lane,
+    /// along with the tag of the lane.
+    #[cfg(feature = "tracing")]
+    #[derive(Default)]
+    struct Recorder {
+        log: std::sync::Arc<Mutex<Vec<(String, String)>>>,
+        tags: Mutex<Vec<String>>,
+        entered: Mutex<Vec<u64>>,
+    }
+
+    #[cfg(feature = "tracing")]
+    impl Recorder {
+        fn record(&self, span: Option<u64>, entry: String) {
+            let tag = span.map_or_else(String::new, |id| {
+                self.tags.lock().unwrap()[id as usize - 1].clone()
+            });
+
+            self.log.lock().unwrap().push((tag, entry));
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    impl tracing::Subscriber for Recorder {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(
+            &self,
+            span: &tracing::span::Attributes<'_>,
+        ) -> tracing::span::Id {
+            let mut tag = Field("tag", String::new());
+            let mut tags = self.tags.lock().unwrap();
+
+            span.record(&mut tag);
+            tags.push(tag.1);
+
+            tracing::span::Id::from_u64(tags.len() as u64)
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {
+        }
+
+        fn record_follows_from(
+            &self,
+            _: &tracing::span::Id,
+            _: &tracing::span::Id,
+        ) {
+        }
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            let mut message = Field("message", String::new());
+            let span = match event.parent() {
+                | Some(id) => Some(id.into_u64()),
+                | None => self.entered.lock().unwrap().last().copied(),
+            };
+
+            event.record(&mut message);
+            self.record(span, message.1);
+        }
+
+        fn enter(&self, span: &tracing::span::Id) {
+            self.entered.lock().unwrap().push(span.into_u64());
+            self.record(Some(span.into_u64()), "enter".into());
+        }
+
+        fn exit(&self, _: &tracing::span::Id) {
+            self.entered.lock().unwrap().pop();
+        }
+    }
+
+    /// A visitor that records a single field by its name.
+    #[cfg(feature = "tracing")]
+    struct Field(&'static str, String);
+
+    #[cfg(feature = "tracing")]
+    impl tracing::field::Visit for Field {
+        fn record_debug(
+            &mut self,
+            field: &tracing::field::Field,
+            value: &dyn fmt::Debug,
+        ) {
+            if field.name() == self.0 {
+                self.1 = format!("{value:?}");
+            }
+        }
     }
 }
@@ -0,0 +1,302 @@
+use std::collections::{HashMap, VecDeque};
+use std::future::poll_fn;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Wake, Waker};
+
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TryRecvError;
+
+/// The default scheduling weight of a lane.
+pub const DEFAULT_WEIGHT: u32 = 1;
+
+/// A receiver that aggregates outgoing messages from all lanes of a
+/// [`Mux`](crate::mux::Mux) created with
+/// [`Mux::scheduled`](crate::mux::Mux::scheduled).
+///
+/// Each lane has its own queue of outgoing messages, and the queues are
+/// interleaved with deficit round robin, so that a chatty lane cannot starve
+/// the others. A lane with weight `n` (see
+/// [`LaneTx::set_weight`](crate::lane::LaneTx::set_weight)) gets to send up to
+/// `n` messages each round.
+#[derive(Debug)]
+pub struct Outgoing<T, V> {
+    outlets: mpsc::UnboundedReceiver<Outlet<T, V>>,
+    opening: bool,
+    lanes: HashMap<u64, Outlet<T, V>>,
+    rotation: VecDeque<u64>,
+    ready: Arc<Ready>,
+    next_id: u64,
+}
+
+/// The outgoing queue of a single lane, as scheduled by [`Outgoing`].
+#[derive(Debug)]
+pub(crate) struct Outlet<T, V> {
+    rx: mpsc::Receiver<(T, V)>,
+    weight: Arc<AtomicU32>,
+    deficit: u32,
+    scheduled: bool,
+    waker: Option<Waker>,
+}
+
+/// A handle through which new lanes get the sender of their outgoing
+/// messages.
+#[derive(Debug)]
+pub(crate) enum Outlets<T, V> {
+    /// All lanes share a single queue.
+    Shared(mpsc::Sender<(T, V)>),
+    /// Each lane registers a queue of its own with an [`Outgoing`].
+    Scheduled {
+        tx: mpsc::UnboundedSender<Outlet<T, V>>,
+        buf: usize,
+    },
+}
+
+/// The lanes whose queues were woken since they were last found empty, so
+/// that idle lanes are never polled.
+#[derive(Debug, Default)]
+struct Ready {
+    state: Mutex<ReadyState>,
+}
+
+#[derive(Debug, Default)]
+struct ReadyState {
+    lanes: VecDeque<u64>,
+    waker: Option<Waker>,
+}
+
+/// The waker of a single lane's queue, which marks the lane as ready.
+#[derive(Debug)]
+struct LaneWaker {
+    id: u64,
+    ready: Arc<Ready>,
+}
+
+impl<T, V> Outgoing<T, V> {
+    /// Creates a new [`Outgoing`], along with the handle to register lanes
+    /// with.
+    ///
+    /// # Parameters
+    /// * `buf` - The buffer size of each lane's queue.
+    #[inline]
+    pub(crate) fn new(buf: usize) -> (Outlets<T, V>, Self) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let outgoing = Self {
+            outlets: rx,
+            opening: true,
+            lanes: HashMap::new(),
+            rotation: VecDeque::new(),
+            ready: Default::default(),
+            next_id: 0,
+        };
+
+        (Outlets::Scheduled { tx, buf }, outgoing)
+    }
+
+    /// Receives the next outgoing message.
+    ///
+    /// # Returns
+    /// * [`Some((tag, value))`] - The next message, along with the tag of the
+    ///   lane it was sent through.
+    /// * [`None`] - If the mux was dropped, and all lane senders were dropped
+    ///   with their queues drained.
+    #[inline]
+    pub async fn recv(&mut self) -> Option<(T, V)> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Attempts to receive the next outgoing message without waiting.
+    ///
+    /// # Returns
+    /// * [`Ok((tag, value))`] - The next message.
+    /// * [`Err(TryRecvError::Empty)`] - If no lane has a message queued.
+    /// * [`Err(TryRecvError::Disconnected)`] - If no more messages will ever be
+    ///   received.
+    pub fn try_recv(&mut self) -> Result<(T, V), TryRecvError> {
+        while self.opening {
+            match self.outlets.try_recv() {
+                | Ok(outlet) => self.schedule(outlet),
+                | Err(TryRecvError::Disconnected) => self.opening = false,
+                | Err(TryRecvError::Empty) => break,
+            }
+        }
+
+        match self.next() {
+            | Poll::Ready(Some(item)) => Ok(item),
+            | Poll::Ready(None) => Err(TryRecvError::Disconnected),
+            | Poll::Pending => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Polls to receive the next outgoing message.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<(T, V)>> {
+        self.ready.register(cx.waker());
+
+        while self.opening {
+            match self.outlets.poll_recv(cx) {
+                | Poll::Ready(Some(outlet)) => self.schedule(outlet),
+                | Poll::Ready(None) => self.opening = false,
+                | Poll::Pending => break,
+            }
+        }
+
+        self.next()
+    }
+
+    /// Schedules the queue of a new lane, which might already hold messages.
+    #[inline]
+    fn schedule(&mut self, mut outlet: Outlet<T, V>) {
+        let id = self.next_id;
+        let waker = LaneWaker {
+            id,
+            ready: self.ready.clone(),
+        };
+
+        self.next_id += 1;
+        outlet.waker = Some(Waker::from(Arc::new(waker)));
+        outlet.scheduled = true;
+        self.lanes.insert(id, outlet);
+        self.rotation.push_back(id);
+    }
+
+    /// Picks the next message with deficit round robin over the ready lanes.
+    fn next(&mut self) -> Poll<Option<(T, V)>> {
+        for id in self.ready.take() {
+            if let Some(outlet) = self.lanes.get_mut(&id) {
+                if !std::mem::replace(&mut outlet.scheduled, true) {
+                    self.rotation.push_back(id);
+                }
+            }
+        }
+
+        while let Some(&id) = self.rotation.front() {
+            let Some(outlet) = self.lanes.get_mut(&id) else {
+                self.rotation.pop_front();
+                continue;
+            };
+
+            if outlet.deficit == 0 {
+                // a new round for this lane
+                outlet.deficit = outlet.weight.load(Ordering::Relaxed).max(1);
+            }
+
+            match outlet.poll_recv() {
+                | Poll::Ready(Some(item)) => {
+                    outlet.deficit -= 1;
+
+                    if outlet.deficit == 0 {
+                        self.rotation.rotate_left(1);
+                    }
+
+                    return Poll::Ready(Some(item));
+                }
+                | Poll::Ready(None) => {
+                    self.lanes.remove(&id);
+                    self.rotation.pop_front();
+                }
+                | Poll::Pending => {
+                    // an idle lane does not keep its deficit, and is only
+                    // scheduled again once its queue is woken
+                    outlet.deficit = 0;
+                    outlet.scheduled = false;
+                    self.rotation.pop_front();
+                }
+            }
+        }
+
+        if !self.opening && self.lanes.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<T, V> Outlet<T, V> {
+    #[inline]
+    fn poll_recv(&mut self) -> Poll<Option<(T, V)>> {
+        let waker = self.waker.as_ref().unwrap_or(Waker::noop());
+
+        self.rx.poll_recv(&mut Context::from_waker(waker))
+    }
+}
+
+impl<T, V> Outlets<T, V> {
+    /// Gets the sender of a new lane's outgoing messages, registering its
+    /// queue if lanes are scheduled.
+    ///
+    /// # Parameters
+    /// * `weight` - The initial scheduling weight of the lane.
+    ///
+    /// # Returns
+    /// The sender of the lane's queue, along with its shared weight if lanes
+    /// are scheduled, or the shared queue otherwise.
+    #[inline]
+    pub(crate) fn open(
+        &self,
+        weight: u32,
+    ) -> (mpsc::Sender<(T, V)>, Option<Arc<AtomicU32>>) {
+        let (outlets, buf) = match *self {
+            | Self::Shared(ref tx) => return (tx.clone(), None),
+            | Self::Scheduled { ref tx, buf } => (tx, buf),
+        };
+        let (tx, rx) = mpsc::channel(buf);
+        let weight = Arc::new(AtomicU32::new(weight));
+
+        // if the receiver is gone, the lane's sender is closed right away
+        _ = outlets.send(Outlet {
+            rx,
+            weight: weight.clone(),
+            deficit: 0,
+            scheduled: false,
+            waker: None,
+        });
+
+        (tx, Some(weight))
+    }
+}
+
+impl Ready {
+    /// Registers the waker of the task receiving outgoing messages.
+    #[inline]
+    fn register(&self, waker: &Waker) {
+        let mut state = self.lock();
+
+        match state.waker {
+            | Some(ref current) if current.will_wake(waker) => {}
+            | _ => state.waker = Some(waker.clone()),
+        }
+    }
+
+    /// Takes the lanes that were woken so far.
+    #[inline]
+    fn take(&self) -> VecDeque<u64> {
+        std::mem::take(&mut self.lock().lanes)
+    }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, ReadyState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Wake for LaneWaker {
+    #[inline]
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    #[inline]
+    fn wake_by_ref(self: &Arc<Self>) {
+        let waker = {
+            let mut state = self.ready.lock();
+
+            state.lanes.push_back(self.id);
+            state.waker.clone()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
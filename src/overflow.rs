@@ -0,0 +1,117 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tokio::sync::Semaphore;
+
+/// What a [`Bus`](crate::bus::Bus) does with a value pushed to a lane whose
+/// buffer is full.
+///
+/// See [`Mux::with_overflow`](crate::mux::Mux::with_overflow) and
+/// [`Mux::set_lane_overflow`](crate::mux::Mux::set_lane_overflow).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Overflow {
+    /// Wait until the lane has room for the value.
+    #[default]
+    Block,
+    /// Drop the value being pushed, keeping the values already buffered.
+    DropNewest,
+    /// Drop the oldest buffered value to make room for the value being
+    /// pushed.
+    DropOldest,
+    /// Fail with [`MuxError::Full`](crate::mux::MuxError::Full), giving the
+    /// value back.
+    Reject,
+    /// Park the value in a queue in front of the lane, so that values pushed
+    /// to other lanes are not held up by it. Parked values are received in
+    /// order once the lane drains.
+    ///
+    /// The number of values parked across all lanes is bounded by a budget
+    /// (see [`Mux::with_park_budget`](crate::mux::Mux::with_park_budget)),
+    /// beyond which pushing waits like [`Overflow::Block`].
+    Park,
+    /// Never consider the lane full, buffering as many values as are pushed.
+    ///
+    /// Strictly speaking, the lane can hold up to
+    /// [`Semaphore::MAX_PERMITS`] values (`usize::MAX >> 3`), past which
+    /// pushing fails like [`Overflow::Reject`], though that many values never
+    /// fit in memory anyway.
+    Unbounded,
+}
+
+/// The budget of values that can be parked with [`Overflow::Park`] across all
+/// lanes of a [`Bus`](crate::bus::Bus), which can be resized while values are
+/// parked.
+#[derive(Debug)]
+pub(crate) struct ParkBudget {
+    permits: Arc<Semaphore>,
+    size: Mutex<Size>,
+}
+
+#[derive(Debug)]
+struct Size {
+    total: usize,
+    // permits held by parked values that are forgotten once given back, as
+    // the budget shrank below the number of parked values
+    debt: usize,
+}
+
+impl ParkBudget {
+    /// Creates a new instance of [`ParkBudget`].
+    ///
+    /// # Parameters
+    /// * `total` - The number of values that can be parked.
+    #[inline]
+    pub(crate) fn new(total: usize) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(total)),
+            size: Mutex::new(Size { total, debt: 0 }),
+        }
+    }
+
+    /// Gets the permits of the budget, one of which is held by each parked
+    /// value.
+    #[inline]
+    pub(crate) const fn permits(&self) -> &Arc<Semaphore> {
+        &self.permits
+    }
+
+    /// Resizes the budget, counting the values parked so far against the new
+    /// size.
+    ///
+    /// # Parameters
+    /// * `total` - The number of values that can be parked.
+    pub(crate) fn resize(&self, total: usize) {
+        let mut size = lock(&self.size);
+
+        if total >= size.total {
+            let grown = total - size.total;
+            let paid = grown.min(size.debt);
+
+            size.debt -= paid;
+            self.permits.add_permits(grown - paid);
+        } else {
+            let shrunk = size.total - total;
+
+            size.debt += shrunk - self.permits.forget_permits(shrunk);
+        }
+
+        size.total = total;
+    }
+
+    /// Gives back the permits of values that are no longer parked.
+    ///
+    /// # Parameters
+    /// * `n` - The number of values.
+    #[inline]
+    pub(crate) fn release(&self, n: usize) {
+        let mut size = lock(&self.size);
+        let paid = n.min(size.debt);
+
+        size.debt -= paid;
+        self.permits.add_permits(n - paid);
+    }
+}
+
+#[inline]
+fn lock(size: &Mutex<Size>) -> MutexGuard<'_, Size> {
+    size.lock().unwrap_or_else(|err| err.into_inner())
+}
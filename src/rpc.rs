@@ -0,0 +1,405 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use tokio::sync::mpsc::error::SendError;
+
+use crate::{CloseReason, Incoming, Key, Lane, LaneRx, LaneTx, Mux, OpenError};
+
+/// An error returned when a call made with [`Client::call`] fails.
+pub enum CallError<T, V> {
+    /// No lane could be opened for the call.
+    Open(OpenError<T>),
+    /// The request could not be sent, as the lane was closed, giving it back.
+    Send(T, V),
+    /// The lane was closed for the given reason before the reply arrived.
+    Closed(T, CloseReason),
+    /// The reply did not arrive within the call's timeout.
+    Timeout(T),
+}
+
+impl<T: fmt::Debug, V> fmt::Debug for CallError<T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            | CallError::Open(err) => f.debug_tuple("Open").field(err).finish(),
+            | CallError::Send(tag, _) => {
+                f.debug_tuple("Send").field(tag).finish_non_exhaustive()
+            }
+            | CallError::Closed(tag, reason) => {
+                f.debug_tuple("Closed").field(tag).field(reason).finish()
+            }
+            | CallError::Timeout(tag) => {
+                f.debug_tuple("Timeout").field(tag).finish()
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug, V> fmt::Display for CallError<T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            | CallError::Open(err) => write!(f, "failed to open a lane: {err}"),
+            | CallError::Send(tag, _) => {
+                write!(f, "lane {tag:?} was closed before sending the request")
+            }
+            | CallError::Closed(tag, reason) => {
+                write!(f, "lane {tag:?} was closed before replying: {reason:?}")
+            }
+            | CallError::Timeout(tag) => {
+                write!(f, "timed out waiting for a reply on lane {tag:?}")
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug, V> std::error::Error for CallError<T, V> {}
+
+/// The calling end of request/response calls over the lanes of a [`Mux`].
+///
+/// Each call takes a lane of its own, opened with a freshly allocated tag
+/// (see [`Mux::with_allocator`](crate::mux::Mux::with_allocator)), which is
+/// closed once the reply arrives. Dropping a pending call closes its lane,
+/// resetting it on the remote end, if any.
+///
+/// This is only available when the `util` feature is enabled.
+#[derive(Debug)]
+pub struct Client<T: Key, V> {
+    mux: Arc<Mux<T, V>>,
+    timeout: Option<Duration>,
+}
+
+impl<T: Key, V> Clone for Client<T, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            mux: self.mux.clone(),
+            timeout: self.timeout,
+        }
+    }
+}
+
+impl<T: Key, V> Client<T, V> {
+    /// Create a new instance of [`Client`], whose calls never time out.
+    ///
+    /// # Parameters
+    /// * `mux` - The mux to open the calls' lanes in, e.g.
+    ///   [`Transport::mux`](crate::transport::Transport::mux).
+    #[inline]
+    pub const fn new(mux: Arc<Mux<T, V>>) -> Self {
+        Self { mux, timeout: None }
+    }
+
+    /// Sets how long calls wait for their replies before failing with
+    /// [`CallError::Timeout`].
+    ///
+    /// # Parameters
+    /// * `timeout` - The timeout of each call.
+    #[inline]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sends a request on a new lane, and waits for a single reply on it.
+    ///
+    /// # Parameters
+    /// * `request` - The request to send.
+    ///
+    /// # Returns
+    /// * [`Ok(reply)`] - The reply to the request.
+    /// * [`Err(CallError)`] - If the call failed.
+    #[inline]
+    pub async fn call(&self, request: V) -> Result<V, CallError<T, V>> {
+        self.call_with(request, self.timeout).await
+    }
+
+    /// Sends a request on a new lane, and waits for a single reply on it for
+    /// up to `timeout`, regardless of the client's timeout.
+    ///
+    /// See [`Client::call`].
+    ///
+    /// # Parameters
+    /// * `request` - The request to send.
+    /// * `timeout` - How long to wait for the reply.
+    #[inline]
+    pub async fn call_timeout(
+        &self,
+        request: V,
+        timeout: Duration,
+    ) -> Result<V, CallError<T, V>> {
+        self.call_with(request, Some(timeout)).await
+    }
+
+    async fn call_with(
+        &self,
+        request: V,
+        timeout: Option<Duration>,
+    ) -> Result<V, CallError<T, V>> {
+        // the lane is dropped once the call is done, which closes it
+        let mut lane = self.mux.open().map_err(CallError::Open)?;
+        let tag = lane.receiver().tag().clone();
+        let call = async {
+            lane.sender().send(request).await.map_err(
+                |SendError((tag, value))| CallError::Send(tag, value),
+            )?;
+
+            lane.receiver()
+                .recv_result()
+                .await
+                .map_err(|reason| CallError::Closed(tag.clone(), reason))
+        };
+
+        match timeout {
+            | Some(timeout) => tokio::time::timeout(timeout, call)
+                .await
+                .unwrap_or_else(|_| Err(CallError::Timeout(tag.clone()))),
+            | None => call.await,
+        }
+    }
+}
+
+/// The request of a call, along with the responder to reply to it with.
+type Call<T, V> = (V, Responder<T, V>);
+
+/// The serving end of request/response calls made with [`Client::call`],
+/// which turns each accepted lane into a request and its [`Responder`].
+///
+/// This is only available when the `util` feature is enabled.
+pub struct Server<T: Key, V> {
+    incoming: Incoming<T, V>,
+    // lanes accepted so far whose requests did not arrive yet
+    pending: FuturesUnordered<BoxFuture<'static, Option<Call<T, V>>>>,
+    accepting: bool,
+}
+
+impl<T: Key, V: fmt::Debug> fmt::Debug for Server<T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Server")
+            .field("incoming", &self.incoming)
+            .field("pending", &self.pending.len())
+            .field("accepting", &self.accepting)
+            .finish()
+    }
+}
+
+impl<T, V> Server<T, V>
+where
+    T: Key + Send + Sync + 'static,
+    V: Send + 'static,
+{
+    /// Create a new instance of [`Server`].
+    ///
+    /// # Parameters
+    /// * `incoming` - The receiver of the lanes calls are made on, e.g. as
+    ///   returned by [`Mux::over`](crate::mux::Mux::over).
+    #[inline]
+    pub fn new(incoming: Incoming<T, V>) -> Self {
+        Self {
+            incoming,
+            pending: FuturesUnordered::new(),
+            accepting: true,
+        }
+    }
+
+    /// Accepts the next call.
+    ///
+    /// Lanes keep being accepted while waiting for their requests, so that a
+    /// caller that never sends its request holds up no other call. Lanes
+    /// that are closed before their request arrives are skipped.
+    ///
+    /// # Returns
+    /// * [`Some((request, responder))`] - The request of the call, along with
+    ///   the responder to reply to it with.
+    /// * [`None`] - If no more lanes can be accepted, and all of the requests
+    ///   of the lanes accepted so far were received.
+    pub async fn accept(&mut self) -> Option<Call<T, V>> {
+        loop {
+            tokio::select! {
+                Some(call) = self.pending.next() => {
+                    if call.is_some() {
+                        return call;
+                    }
+                }
+                lane = self.incoming.accept(), if self.accepting => {
+                    match lane {
+                        | Some(lane) => {
+                            self.pending.push(Box::pin(Self::request(lane)));
+                        }
+                        | None => self.accepting = false,
+                    }
+                }
+                else => return None,
+            }
+        }
+    }
+
+    /// Waits for the request of a call made on the given lane.
+    ///
+    /// # Parameters
+    /// * `lane` - The lane the call is made on.
+    ///
+    /// # Returns
+    /// * [`Some((request, responder))`] - The request of the call, along with
+    ///   the responder to reply to it with.
+    /// * [`None`] - If the lane was closed before the request arrived.
+    pub async fn request(lane: Lane<T, V>) -> Option<Call<T, V>> {
+        let (tx, mut rx) = lane.split();
+        let request = rx.recv().await?;
+
+        Some((request, Responder { rx, tx }))
+    }
+}
+
+impl<T, V> From<Incoming<T, V>> for Server<T, V>
+where
+    T: Key + Send + Sync + 'static,
+    V: Send + 'static,
+{
+    #[inline]
+    fn from(incoming: Incoming<T, V>) -> Self {
+        Self::new(incoming)
+    }
+}
+
+/// A handle to reply to a single call accepted with [`Server::accept`].
+///
+/// Dropping the responder without replying closes the call's lane, which
+/// fails the call with [`CallError::Closed`].
+#[derive(Debug)]
+pub struct Responder<T: Key, V> {
+    // the receiver is dropped first, as with a whole lane
+    rx: LaneRx<T, V>,
+    tx: LaneTx<T, V>,
+}
+
+impl<T: Key, V> Responder<T, V> {
+    /// Gets the tag of the call's lane.
+    #[inline]
+    pub const fn tag(&self) -> &T {
+        self.tx.tag()
+    }
+
+    /// Replies to the call, closing its lane afterwards.
+    ///
+    /// # Parameters
+    /// * `reply` - The reply to the call's request.
+    ///
+    /// # Returns
+    /// * [`Ok(())`] - If the reply was sent.
+    /// * [`Err(SendError)`] - If the lane was closed, e.g. as the call was
+    ///   cancelled, giving the reply back.
+    #[inline]
+    pub async fn respond(mut self, reply: V) -> Result<(), SendError<(T, V)>> {
+        self.tx.send(reply).await
+    }
+
+    /// Waits until the caller gives up on the call, e.g. as it timed out or
+    /// was cancelled, which closes the call's lane.
+    ///
+    /// # Returns
+    /// Why the lane was closed.
+    pub async fn cancelled(&mut self) -> CloseReason {
+        loop {
+            // anything sent after the request is ignored
+            if let Err(reason) = self.rx.recv_result().await {
+                return reason;
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "transport"))]
+mod tests {
+    use futures::SinkExt;
+    use tokio_util::codec::Framed;
+
+    use super::*;
+    use crate::codec::{StrCodec, VarintCodec};
+    use crate::transport::{Frame, WireCodec, CANCEL};
+    use crate::Parity;
+
+    type TestCodec = WireCodec<VarintCodec<u32>, StrCodec>;
+
+    #[tokio::test]
+    async fn rpc_test() {
+        let (client_io, server_io) = tokio::io::duplex(256);
+        let (transport, _lanes) =
+            Mux::over(client_io, TestCodec::default(), 8, 4);
+        let transport = transport.with_allocator(Parity::<u32>::odd());
+        let client =
+            Client::new(transport.mux()).with_timeout(Duration::from_secs(5));
+        let (server_transport, lanes) =
+            Mux::over(server_io, TestCodec::default(), 8, 4);
+        let (cancelled_tx, cancelled_rx) = tokio::sync::oneshot::channel();
+
+        tokio::spawn(transport.run());
+        tokio::spawn(server_transport.run());
+        tokio::spawn(async move {
+            let mut server = Server::new(lanes);
+            let mut cancelled_tx = Some(cancelled_tx);
+
+            while let Some((request, mut responder)) = server.accept().await {
+                if request == "slow" {
+                    let reason = responder.cancelled().await;
+
+                    _ = cancelled_tx.take().unwrap().send(reason);
+                } else {
+                    responder.respond(request.to_uppercase()).await.unwrap();
+                }
+            }
+        });
+
+        assert_eq!("HI", client.call("hi".to_owned()).await.unwrap());
+
+        // each call takes a lane of its own
+        let (a, b) = tokio::join!(
+            client.call("a".to_owned()),
+            client.call("b".to_owned())
+        );
+
+        assert_eq!(("A".to_owned(), "B".to_owned()), (a.unwrap(), b.unwrap()));
+
+        // timing out resets the call's lane
+        let err = client
+            .call_timeout("slow".to_owned(), Duration::from_millis(50))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, CallError::Timeout(tag) if tag % 2 == 1));
+        assert!(matches!(
+            cancelled_rx.await.unwrap(),
+            CloseReason::Reset(CANCEL)
+        ));
+
+        assert_eq!("BYE", client.call("bye".to_owned()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn rpc_server_test() {
+        let (client_io, server_io) = tokio::io::duplex(256);
+        let (transport, lanes) =
+            Mux::over(server_io, TestCodec::default(), 8, 4);
+        let mut client = Framed::new(client_io, TestCodec::default());
+
+        tokio::spawn(transport.run());
+        tokio::spawn(async move {
+            let mut server = Server::new(lanes);
+
+            while let Some((request, responder)) = server.accept().await {
+                responder.respond(request.to_uppercase()).await.unwrap();
+            }
+        });
+
+        // a caller that never sends its request holds up no other call
+        client.send(Frame::Open(1)).await.unwrap();
+        client.send(Frame::Data(3, "hi".to_owned())).await.unwrap();
+
+        assert_eq!(
+            Frame::Data(3, "HI".to_owned()),
+            client.next().await.unwrap().unwrap()
+        );
+    }
+}
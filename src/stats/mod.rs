@@ -0,0 +1,353 @@
+#[cfg(feature = "prometheus")]
+mod prometheus;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use tokio::sync::mpsc::WeakSender;
+use tokio::time::Instant;
+
+use crate::admission::AdmissionStats;
+use crate::lane::Activity;
+
+/// The number of buckets of a [`Histogram`], the last of which has no upper
+/// bound.
+pub const BUCKETS: usize = 24;
+
+/// A snapshot of the statistics of a [`Mux`](crate::mux::Mux).
+///
+/// See [`Mux::stats`](crate::mux::Mux::stats).
+#[derive(Debug, Clone)]
+pub struct MuxStats<T> {
+    /// The statistics of each open lane, along with its tag.
+    pub lanes: Vec<(T, LaneStats)>,
+    /// The message counts and latencies of all lanes, including closed ones.
+    pub totals: Totals,
+    /// How often lane admission control kicked in.
+    pub admission: AdmissionStats,
+}
+
+/// A snapshot of the statistics of a lane.
+///
+/// See [`LaneRx::stats`](crate::lane::LaneRx::stats) and
+/// [`LaneTx::stats`](crate::lane::LaneTx::stats).
+#[derive(Debug, Clone)]
+pub struct LaneStats {
+    /// The message counts and latencies of the lane.
+    pub totals: Totals,
+    /// The messages waiting to be received from the lane, including parked
+    /// ones (see [`Overflow::Park`](crate::overflow::Overflow::Park)).
+    pub inbound: Depth,
+    /// The messages sent through the lane that are waiting to be written out
+    /// (see [`Outgoing`](crate::outgoing::Outgoing)), only reported by lanes
+    /// that have a queue of their own.
+    pub outbound: Depth,
+    /// When the lane was created.
+    pub created: Instant,
+    /// When the lane last saw traffic in either direction.
+    pub last_active: Instant,
+}
+
+/// Message counts and latencies, of a single lane or of a whole mux.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Totals {
+    /// The number of messages pushed to lanes by the mux.
+    pub received: u64,
+    /// The number of messages received from lanes through their
+    /// [`LaneRx`](crate::lane::LaneRx).
+    pub consumed: u64,
+    /// The number of messages pushed to lanes that were dropped by their
+    /// overflow policy, or discarded as the lane was evicted or aborted.
+    pub dropped: u64,
+    /// The number of messages sent through lanes'
+    /// [`LaneTx`](crate::lane::LaneTx).
+    pub sent: u64,
+    /// How long messages waited in lanes from being pushed to being received.
+    pub latency: Histogram,
+}
+
+/// How many messages are waiting in a queue, and how many it can hold.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Depth {
+    /// The number of messages waiting in the queue.
+    pub len: usize,
+    /// The number of messages the queue can hold, or [`None`] if it is
+    /// unbounded.
+    pub capacity: Option<usize>,
+}
+
+/// A histogram of durations, with exponential buckets from 1µs up to about
+/// 4s, and a last bucket for anything longer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    buckets: [u64; BUCKETS],
+    sum: Duration,
+}
+
+impl Default for Histogram {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            buckets: [0; BUCKETS],
+            sum: Duration::ZERO,
+        }
+    }
+}
+
+impl Histogram {
+    /// Gets the number of recorded durations.
+    #[inline]
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// Gets the sum of the recorded durations.
+    #[inline]
+    pub const fn sum(&self) -> Duration {
+        self.sum
+    }
+
+    /// Gets the mean of the recorded durations.
+    ///
+    /// # Returns
+    /// * [`Some(mean)`] - The mean of the recorded durations.
+    /// * [`None`] - If no durations were recorded.
+    #[inline]
+    pub fn mean(&self) -> Option<Duration> {
+        let count = u32::try_from(self.count()).unwrap_or(u32::MAX);
+
+        (count > 0).then(|| self.sum / count)
+    }
+
+    /// Gets the upper bound of the bucket holding the `q`-quantile of the
+    /// recorded durations.
+    ///
+    /// # Parameters
+    /// * `q` - The quantile, between `0.0` and `1.0`.
+    ///
+    /// # Returns
+    /// * [`Some(bound)`] - The upper bound of the bucket, which is
+    ///   [`Duration::MAX`] for the last bucket.
+    /// * [`None`] - If no durations were recorded.
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        let count = self.count();
+
+        if count == 0 {
+            return None;
+        }
+
+        let rank = ((count as f64) * q.clamp(0.0, 1.0)).ceil().max(1.0) as u64;
+        let mut seen = 0;
+
+        self.buckets().find_map(|(bound, n)| {
+            seen += n;
+            (seen >= rank).then(|| bound.unwrap_or(Duration::MAX))
+        })
+    }
+
+    /// Iterates over the buckets of the histogram.
+    ///
+    /// # Returns
+    /// The upper bound of each bucket, or [`None`] for the last one, along
+    /// with the number of durations recorded in it.
+    #[inline]
+    pub fn buckets(
+        &self,
+    ) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .map(|(idx, &n)| (Self::bound(idx), n))
+    }
+
+    /// Gets the upper bound of the bucket at `idx`.
+    #[inline]
+    fn bound(idx: usize) -> Option<Duration> {
+        (idx + 1 < BUCKETS).then(|| Duration::from_micros(1 << idx))
+    }
+
+    /// Gets the index of the bucket that `duration` falls in.
+    #[inline]
+    fn index(duration: Duration) -> usize {
+        let micros = duration.as_micros();
+
+        match micros {
+            | 0 | 1 => 0,
+            | _ => (u128::BITS - (micros - 1).leading_zeros()) as usize,
+        }
+        .min(BUCKETS - 1)
+    }
+}
+
+/// The live counterpart of [`Totals`], updated as messages flow.
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    received: AtomicU64,
+    consumed: AtomicU64,
+    dropped: AtomicU64,
+    sent: AtomicU64,
+    buckets: [AtomicU64; BUCKETS],
+    sum: AtomicU64,
+}
+
+impl Counters {
+    /// Takes a snapshot of the counters.
+    pub(crate) fn snapshot(&self) -> Totals {
+        let latency = Histogram {
+            buckets: std::array::from_fn(|idx| {
+                self.buckets[idx].load(Ordering::Relaxed)
+            }),
+            sum: Duration::from_nanos(self.sum.load(Ordering::Relaxed)),
+        };
+
+        Totals {
+            received: self.received.load(Ordering::Relaxed),
+            consumed: self.consumed.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            sent: self.sent.load(Ordering::Relaxed),
+            latency,
+        }
+    }
+
+    #[inline]
+    fn consume(&self, latency: Duration) {
+        let nanos = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+
+        self.consumed.fetch_add(1, Ordering::Relaxed);
+        self.buckets[Histogram::index(latency)].fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+/// The statistics of a single lane, shared by its halves, which also roll up
+/// into the totals of its mux.
+///
+/// With the `tracing` feature, this also holds the lane's span, which lasts
+/// for as long as the lane does.
+#[derive(Debug)]
+pub(crate) struct Meter<T, V> {
+    lane: Counters,
+    totals: Arc<Counters>,
+    activity: Activity,
+    capacity: Option<usize>,
+    outlet: OnceLock<WeakSender<(T, V)>>,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl<T, V> Meter<T, V> {
+    /// Creates a new instance of [`Meter`], as of now.
+    ///
+    /// # Parameters
+    /// * `totals` - The counters of the mux the lane belongs to.
+    /// * `capacity` - The capacity of the lane's inbound queue, if bounded.
+    /// * `idle` - How long the lane can be idle before it is evicted, if ever.
+    #[inline]
+    pub(crate) fn new(
+        totals: Arc<Counters>,
+        capacity: Option<usize>,
+        idle: Option<Duration>,
+    ) -> Self {
+        Self {
+            lane: Default::default(),
+            totals,
+            activity: Activity::new(idle),
+            capacity,
+            outlet: OnceLock::new(),
+            #[cfg(feature = "tracing")]
+            span: tracing::Span::none(),
+        }
+    }
+
+    /// Makes the lane traced in `span`.
+    ///
+    /// # Parameters
+    /// * `span` - The span of the lane.
+    #[cfg(feature = "tracing")]
+    #[inline]
+    pub(crate) fn instrumented(mut self, span: tracing::Span) -> Self {
+        self.span = span;
+        self
+    }
+
+    /// Gets the span of the lane.
+    #[cfg(feature = "tracing")]
+    #[inline]
+    pub(crate) const fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+
+    /// Attaches the lane's outbound queue, so that its depth is reported.
+    #[inline]
+    pub(crate) fn attach(&self, outlet: WeakSender<(T, V)>) {
+        _ = self.outlet.set(outlet);
+    }
+
+    /// Gets the traffic of the lane.
+    #[inline]
+    pub(crate) const fn activity(&self) -> &Activity {
+        &self.activity
+    }
+
+    /// Records a message pushed to the lane.
+    #[inline]
+    pub(crate) fn received(&self) {
+        self.lane.received.fetch_add(1, Ordering::Relaxed);
+        self.totals.received.fetch_add(1, Ordering::Relaxed);
+        self.activity.touch();
+    }
+
+    /// Records a message received from the lane, which was pushed at `at`.
+    #[inline]
+    pub(crate) fn consumed(&self, at: Instant) {
+        let latency = at.elapsed();
+
+        self.lane.consume(latency);
+        self.totals.consume(latency);
+    }
+
+    /// Records messages pushed to the lane that were dropped.
+    #[inline]
+    pub(crate) fn dropped(&self, count: u64) {
+        self.lane.dropped.fetch_add(count, Ordering::Relaxed);
+        self.totals.dropped.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Records a message sent through the lane.
+    #[inline]
+    pub(crate) fn sent(&self) {
+        self.lane.sent.fetch_add(1, Ordering::Relaxed);
+        self.totals.sent.fetch_add(1, Ordering::Relaxed);
+        self.activity.touch();
+    }
+
+    /// Takes a snapshot of the lane's statistics.
+    pub(crate) fn snapshot(&self) -> LaneStats {
+        let totals = self.lane.snapshot();
+        // a message might be received before it is recorded as pushed
+        let queued = totals
+            .received
+            .saturating_sub(totals.consumed)
+            .saturating_sub(totals.dropped);
+        let inbound = Depth {
+            len: usize::try_from(queued).unwrap_or(usize::MAX),
+            capacity: self.capacity,
+        };
+        let outbound = match self.outlet.get().and_then(WeakSender::upgrade) {
+            | Some(tx) => Depth {
+                len: tx.max_capacity() - tx.capacity(),
+                capacity: Some(tx.max_capacity()),
+            },
+            | None => Depth::default(),
+        };
+
+        LaneStats {
+            totals,
+            inbound,
+            outbound,
+            created: self.activity.since(),
+            last_active: self.activity.last_seen(),
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use std::fmt::{self, Debug, Write};
+
+use super::{Histogram, LaneStats, MuxStats, Totals};
+
+impl<T: Debug> MuxStats<T> {
+    /// Renders the statistics in the Prometheus text exposition format.
+    ///
+    /// Lanes are labeled with the [`Debug`] representation of their tags.
+    ///
+    /// This is only available when the `prometheus` feature is enabled.
+    #[inline]
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        // writing to a string never fails
+        _ = self.write_prometheus(&mut out);
+
+        out
+    }
+
+    /// Writes the statistics in the Prometheus text exposition format.
+    ///
+    /// See [`MuxStats::to_prometheus`].
+    ///
+    /// # Parameters
+    /// * `out` - The writer to write the statistics to.
+    pub fn write_prometheus<W: Write>(&self, out: &mut W) -> fmt::Result {
+        let Totals {
+            received,
+            consumed,
+            dropped,
+            sent,
+            ref latency,
+        } = self.totals;
+
+        gauge(
+            out,
+            "rexer_lanes",
+            "The number of open lanes.",
+            self.lanes.len(),
+        )?;
+        counter(
+            out,
+            "rexer_messages_received",
+            "Messages pushed to lanes.",
+            received,
+        )?;
+        counter(
+            out,
+            "rexer_messages_consumed",
+            "Messages received from lanes.",
+            consumed,
+        )?;
+        counter(
+            out,
+            "rexer_messages_dropped",
+            "Messages dropped by lanes.",
+            dropped,
+        )?;
+        counter(
+            out,
+            "rexer_messages_sent",
+            "Messages sent through lanes.",
+            sent,
+        )?;
+        counter(
+            out,
+            "rexer_lanes_rejected",
+            "Lanes refused over the lane limit.",
+            self.admission.rejected,
+        )?;
+        counter(
+            out,
+            "rexer_lanes_throttled",
+            "Lanes refused over the creation rate.",
+            self.admission.throttled,
+        )?;
+        counter(
+            out,
+            "rexer_lanes_evicted",
+            "Lanes evicted to make room.",
+            self.admission.evicted,
+        )?;
+        histogram(
+            out,
+            "rexer_queue_latency_seconds",
+            "Time messages waited in lanes before being received.",
+            latency,
+        )?;
+
+        let per_lane: [Metric; 5] = [
+            (
+                "rexer_lane_inbound_depth",
+                "gauge",
+                "Messages waiting to be received from the lane.",
+                |lane| lane.inbound.len as u64,
+            ),
+            (
+                "rexer_lane_outbound_depth",
+                "gauge",
+                "Messages sent through the lane waiting to be written out.",
+                |lane| lane.outbound.len as u64,
+            ),
+            (
+                "rexer_lane_messages_received_total",
+                "counter",
+                "Messages pushed to the lane.",
+                |lane| lane.totals.received,
+            ),
+            (
+                "rexer_lane_messages_consumed_total",
+                "counter",
+                "Messages received from the lane.",
+                |lane| lane.totals.consumed,
+            ),
+            (
+                "rexer_lane_messages_sent_total",
+                "counter",
+                "Messages sent through the lane.",
+                |lane| lane.totals.sent,
+            ),
+        ];
+
+        for (name, kind, help, value) in per_lane {
+            header(out, name, kind, help)?;
+
+            for (tag, lane) in &self.lanes {
+                writeln!(
+                    out,
+                    "{name}{{tag=\"{}\"}} {}",
+                    Label(tag),
+                    value(lane)
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A per-lane metric: its name, type, help text and how to get its value.
+type Metric = (
+    &'static str,
+    &'static str,
+    &'static str,
+    fn(&LaneStats) -> u64,
+);
+
+/// A label value, escaped as per the Prometheus text exposition format.
+struct Label<'a, T>(&'a T);
+
+impl<T: Debug> fmt::Display for Label<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ch in format!("{:?}", self.0).chars() {
+            match ch {
+                | '\\' => f.write_str("\\\\")?,
+                | '"' => f.write_str("\\\"")?,
+                | '\n' => f.write_str("\\n")?,
+                | ch => f.write_char(ch)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[inline]
+fn header<W: Write>(
+    out: &mut W,
+    name: &str,
+    kind: &str,
+    help: &str,
+) -> fmt::Result {
+    writeln!(out, "# HELP {name} {help}")?;
+    writeln!(out, "# TYPE {name} {kind}")
+}
+
+#[inline]
+fn gauge<W: Write>(
+    out: &mut W,
+    name: &str,
+    help: &str,
+    value: usize,
+) -> fmt::Result {
+    header(out, name, "gauge", help)?;
+    writeln!(out, "{name} {value}")
+}
+
+#[inline]
+fn counter<W: Write>(
+    out: &mut W,
+    name: &str,
+    help: &str,
+    value: u64,
+) -> fmt::Result {
+    let name = format!("{name}_total");
+
+    header(out, &name, "counter", help)?;
+    writeln!(out, "{name} {value}")
+}
+
+fn histogram<W: Write>(
+    out: &mut W,
+    name: &str,
+    help: &str,
+    histogram: &Histogram,
+) -> fmt::Result {
+    let mut count = 0;
+
+    header(out, name, "histogram", help)?;
+
+    for (bound, n) in histogram.buckets() {
+        count += n;
+
+        match bound {
+            | Some(bound) => writeln!(
+                out,
+                "{name}_bucket{{le=\"{}\"}} {count}",
+                bound.as_secs_f64()
+            )?,
+            | None => writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {count}")?,
+        }
+    }
+
+    writeln!(out, "{name}_sum {}", histogram.sum().as_secs_f64())?;
+    writeln!(out, "{name}_count {count}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Mux;
+
+    #[tokio::test]
+    async fn prometheus_test() {
+        let (mut mux, _mux_rx) = Mux::new(8, 8);
+        let mut lane = mux.send("a\"b", 1).await.unwrap().lane().unwrap();
+
+        assert_eq!(Some(1), lane.receiver().recv().await);
+
+        let text = mux.stats().to_prometheus();
+
+        assert!(text.contains("# TYPE rexer_lanes gauge\nrexer_lanes 1\n"));
+        assert!(text.contains("rexer_messages_consumed_total 1\n"));
+        assert!(text
+            .contains("rexer_queue_latency_seconds_bucket{le=\"+Inf\"} 1\n"));
+        assert!(text.contains("rexer_queue_latency_seconds_count 1\n"));
+        assert!(text.contains(
+            "rexer_lane_messages_received_total{tag=\"\\\"a\\\\\\\"b\\\"\"} \
+             1\n"
+        ));
+    }
+}
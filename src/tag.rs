@@ -0,0 +1,93 @@
+/// A source of fresh tags for lanes opened with
+/// [`Mux::open`](crate::mux::Mux::open).
+///
+/// This is implemented for closures returning [`Option<T>`], so that
+/// non-integer tags can be allocated as well.
+pub trait TagAllocator<T>: Send {
+    /// Allocates the next tag.
+    ///
+    /// # Returns
+    /// * [`Some(tag)`] - The allocated tag.
+    /// * [`None`] - If no more tags are available.
+    fn allocate(&mut self) -> Option<T>;
+}
+
+impl<T, F> TagAllocator<T> for F
+where
+    F: FnMut() -> Option<T> + Send,
+{
+    #[inline]
+    fn allocate(&mut self) -> Option<T> {
+        self()
+    }
+}
+
+/// A [`TagAllocator`] of either odd or even integer tags, so that both ends
+/// of a connection can open lanes without their tags ever colliding.
+///
+/// Zero is never allocated, as it is reserved by some protocols (e.g. yamux).
+#[derive(Debug, Clone, Copy)]
+pub struct Parity<T> {
+    next: Option<T>,
+}
+
+macro_rules! impl_parity {
+    ($($ty:ty),*) => {
+        $(
+            impl Parity<$ty> {
+                /// Creates an allocator of odd tags, starting at one.
+                #[inline]
+                pub const fn odd() -> Self {
+                    Self { next: Some(1) }
+                }
+
+                /// Creates an allocator of even tags, starting at two.
+                #[inline]
+                pub const fn even() -> Self {
+                    Self { next: Some(2) }
+                }
+            }
+
+            impl TagAllocator<$ty> for Parity<$ty> {
+                #[inline]
+                fn allocate(&mut self) -> Option<$ty> {
+                    let tag = self.next?;
+
+                    self.next = tag.checked_add(2);
+
+                    Some(tag)
+                }
+            }
+        )*
+    };
+}
+
+impl_parity!(u8, u16, u32, u64, u128, usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parity_test() {
+        let mut odd = Parity::<u8>::odd();
+        let mut even = Parity::<u8>::even();
+
+        let odds: Vec<_> = std::iter::from_fn(|| odd.allocate()).collect();
+        let evens: Vec<_> = std::iter::from_fn(|| even.allocate()).collect();
+
+        assert_eq!(128, odds.len());
+        assert_eq!(127, evens.len());
+        assert!(odds.iter().all(|tag| tag % 2 == 1));
+        assert!(evens.iter().all(|tag| tag % 2 == 0 && *tag != 0));
+        assert_eq!((Some(&1), Some(&255)), (odds.first(), odds.last()));
+        assert_eq!((Some(&2), Some(&254)), (evens.first(), evens.last()));
+
+        let mut tags = ["a", "b"].into_iter();
+        let mut alloc = move || tags.next();
+
+        assert_eq!(Some("a"), alloc.allocate());
+        assert_eq!(Some("b"), alloc.allocate());
+        assert_eq!(None, alloc.allocate());
+    }
+}
@@ -0,0 +1,556 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::{FromStr, Split};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::{Bus, DeliveryReport, LaneRx, OpenError};
+
+/// The separator between the levels of a topic, e.g. `orders.eu.created`.
+pub const SEPARATOR: char = '.';
+
+/// The wildcard level of a [`Pattern`] that matches exactly one level of a
+/// topic.
+pub const SINGLE_LEVEL: &str = "*";
+
+/// The wildcard level of a [`Pattern`] that matches any number of trailing
+/// levels of a topic, including none.
+pub const MULTI_LEVEL: &str = "#";
+
+/// A topic a value was published to, as received by subscribers.
+pub type Topic = Arc<str>;
+
+/// An error returned when parsing a [`Pattern`] fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternError {
+    /// A level of the pattern is empty, e.g. `orders..created`.
+    EmptyLevel,
+    /// A level of the pattern mixes a wildcard with other characters, e.g.
+    /// `orders.eu*`.
+    PartialWildcard,
+    /// The multi-level wildcard is not the last level of the pattern, e.g.
+    /// `orders.#.created`.
+    MisplacedMultiLevel,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            | PatternError::EmptyLevel => {
+                f.write_str("pattern has an empty level")
+            }
+            | PatternError::PartialWildcard => {
+                f.write_str("wildcards must take up a whole level")
+            }
+            | PatternError::MisplacedMultiLevel => {
+                f.write_str("the multi-level wildcard must be the last level")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+/// A pattern of topics to subscribe to, made of levels separated by
+/// [`SEPARATOR`].
+///
+/// Each level either matches the same level of a topic exactly, or is one of
+/// the wildcards:
+/// * [`SINGLE_LEVEL`] (`*`) - Matches exactly one level, e.g.
+///   `orders.*.created` matches `orders.eu.created`, but not `orders.created`.
+/// * [`MULTI_LEVEL`] (`#`) - Matches any number of trailing levels, including
+///   none, e.g. `orders.#` matches `orders`, `orders.eu` and
+///   `orders.eu.created`. It can only be the last level.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Pattern(Arc<str>);
+
+/// A single level of a [`Pattern`].
+#[derive(Debug, Clone, Copy)]
+enum Level<'a> {
+    Exact(&'a str),
+    Single,
+    Multi,
+}
+
+impl Pattern {
+    /// Parses a new [`Pattern`].
+    ///
+    /// # Parameters
+    /// * `pattern` - The pattern to parse, e.g. `orders.*.created`.
+    ///
+    /// # Returns
+    /// * [`Ok(pattern)`] - The parsed pattern.
+    /// * [`Err(PatternError)`] - If the pattern is malformed.
+    pub fn new(pattern: &str) -> Result<Self, PatternError> {
+        let mut levels = pattern.split(SEPARATOR).peekable();
+
+        while let Some(level) = levels.next() {
+            if level.is_empty() {
+                return Err(PatternError::EmptyLevel);
+            }
+
+            if level == MULTI_LEVEL && levels.peek().is_some() {
+                return Err(PatternError::MisplacedMultiLevel);
+            }
+
+            if level.len() > 1 && level.contains(['*', '#']) {
+                return Err(PatternError::PartialWildcard);
+            }
+        }
+
+        Ok(Self(pattern.into()))
+    }
+
+    /// Gets the pattern as a string.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Gets whether the pattern matches the given topic or not.
+    ///
+    /// # Parameters
+    /// * `topic` - The topic to match, e.g. `orders.eu.created`.
+    pub fn matches(&self, topic: &str) -> bool {
+        let mut topic = topic.split(SEPARATOR);
+
+        for level in self.levels() {
+            match (level, topic.next()) {
+                | (Level::Multi, _) => return true,
+                | (Level::Single, Some(_)) => {}
+                | (Level::Exact(level), Some(got)) if level == got => {}
+                | _ => return false,
+            }
+        }
+
+        topic.next().is_none()
+    }
+
+    /// Iterates over the levels of the pattern.
+    #[inline]
+    fn levels(&self) -> impl Iterator<Item = Level<'_>> {
+        self.0.split(SEPARATOR).map(|level| match level {
+            | SINGLE_LEVEL => Level::Single,
+            | MULTI_LEVEL => Level::Multi,
+            | level => Level::Exact(level),
+        })
+    }
+}
+
+impl fmt::Debug for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = PatternError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<&str> for Pattern {
+    type Error = PatternError;
+
+    #[inline]
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+/// The tag of the lane backing a [`Subscription`], which tells subscriptions
+/// to the same pattern apart.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Subscriber {
+    id: u64,
+    pattern: Pattern,
+}
+
+impl Subscriber {
+    /// Gets the unique id of the subscription.
+    #[inline]
+    pub const fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Gets the pattern of topics subscribed to.
+    #[inline]
+    pub const fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+}
+
+/// A publish/subscribe bus of values published to dotted topics, on top of a
+/// [`Bus`].
+///
+/// Each [`Subscription`] is backed by its own lane, which receives every
+/// value published to a topic matching the subscription's [`Pattern`], as per
+/// the lane's overflow policy. Matching subscriptions are looked up in a trie
+/// of patterns, so publishing stays fast with many subscriptions.
+#[derive(Debug)]
+pub struct TopicBus<V> {
+    bus: Bus<Subscriber, (Topic, V)>,
+    subscribers: Arc<RwLock<Node>>,
+    next_id: AtomicU64,
+}
+
+impl<V> TopicBus<V> {
+    /// Create a new instance of [`TopicBus`].
+    ///
+    /// # Parameters
+    /// * `lane_buf` - The buffer size for each subscription's lane.
+    #[inline]
+    pub fn new(lane_buf: usize) -> Self {
+        Bus::new(lane_buf).into()
+    }
+
+    /// Gets a reference to the underlying bus, e.g. to close subscriptions'
+    /// lanes or to get their statistics.
+    #[inline]
+    pub const fn bus(&self) -> &Bus<Subscriber, (Topic, V)> {
+        &self.bus
+    }
+
+    /// Gets a mutable reference to the underlying bus, e.g. to set the
+    /// overflow policy of subscriptions' lanes.
+    #[inline]
+    pub fn bus_mut(&mut self) -> &mut Bus<Subscriber, (Topic, V)> {
+        &mut self.bus
+    }
+
+    /// Subscribes to the topics matching a pattern.
+    ///
+    /// The subscription lasts until it is dropped.
+    ///
+    /// # Parameters
+    /// * `pattern` - The pattern of topics to subscribe to.
+    ///
+    /// # Returns
+    /// * [`Ok(Subscription)`] - The new subscription.
+    /// * [`Err(OpenError::Refused)`] - If the subscription's lane was refused
+    ///   by the lane limit or creation rate.
+    pub fn subscribe(
+        &self,
+        pattern: Pattern,
+    ) -> Result<Subscription<V>, OpenError<Subscriber>> {
+        let subscriber = Subscriber {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            pattern,
+        };
+        let rx = self.bus.open(subscriber.clone())?;
+
+        write(&self.subscribers).insert(subscriber);
+
+        Ok(Subscription {
+            rx,
+            subscribers: self.subscribers.clone(),
+        })
+    }
+
+    /// Gets the subscribers whose patterns match the given topic.
+    ///
+    /// # Parameters
+    /// * `topic` - The topic to match, e.g. `orders.eu.created`.
+    pub fn matching(&self, topic: &str) -> Vec<Subscriber> {
+        let mut matching = Vec::new();
+
+        read(&self.subscribers).collect(topic.split(SEPARATOR), &mut matching);
+
+        matching
+    }
+
+    /// Publishes a value to a topic, pushing a copy of it to every matching
+    /// subscription, as per each subscription's overflow policy.
+    ///
+    /// See [`Bus::multicast`].
+    ///
+    /// # Parameters
+    /// * `topic` - The topic to publish to, e.g. `orders.eu.created`.
+    /// * `value` - The value to publish.
+    ///
+    /// # Returns
+    /// The outcome of pushing the value to each matching subscription, where
+    /// subscriptions whose lanes were closed by the bus are reported as
+    /// failed.
+    pub async fn publish(
+        &self,
+        topic: &str,
+        value: V,
+    ) -> DeliveryReport<Subscriber, (Topic, V)>
+    where
+        V: Clone,
+    {
+        let matching = self.matching(topic);
+
+        self.bus.multicast(matching, (topic.into(), value)).await
+    }
+}
+
+impl<V> From<Bus<Subscriber, (Topic, V)>> for TopicBus<V> {
+    #[inline]
+    fn from(bus: Bus<Subscriber, (Topic, V)>) -> Self {
+        Self {
+            bus,
+            subscribers: Default::default(),
+            next_id: AtomicU64::new(0),
+        }
+    }
+}
+
+/// A subscription to the topics matching a [`Pattern`], made with
+/// [`TopicBus::subscribe`].
+///
+/// Dropping the subscription unsubscribes it.
+#[derive(Debug)]
+pub struct Subscription<V> {
+    rx: LaneRx<Subscriber, (Topic, V)>,
+    subscribers: Arc<RwLock<Node>>,
+}
+
+impl<V> Subscription<V> {
+    /// Gets the subscriber, which is the tag of the subscription's lane.
+    #[inline]
+    pub const fn subscriber(&self) -> &Subscriber {
+        self.rx.tag()
+    }
+
+    /// Gets the pattern of topics subscribed to.
+    #[inline]
+    pub const fn pattern(&self) -> &Pattern {
+        self.subscriber().pattern()
+    }
+
+    /// Gets a reference to the receiver of the subscription's lane.
+    #[inline]
+    pub fn receiver(&mut self) -> &mut LaneRx<Subscriber, (Topic, V)> {
+        &mut self.rx
+    }
+
+    /// Receives the next value published to a matching topic.
+    ///
+    /// # Returns
+    /// * [`Some((topic, value))`] - The value, along with the topic it was
+    ///   published to.
+    /// * [`None`] - If the subscription's lane was closed.
+    #[inline]
+    pub async fn recv(&mut self) -> Option<(Topic, V)> {
+        self.rx.recv().await
+    }
+}
+
+impl<V> Drop for Subscription<V> {
+    #[inline]
+    fn drop(&mut self) {
+        let subscriber = self.rx.tag();
+        let levels: Vec<_> = subscriber.pattern.levels().collect();
+
+        write(&self.subscribers).remove(&levels, subscriber.id);
+    }
+}
+
+/// A node of the trie of subscribers' patterns, one per distinct prefix of
+/// levels.
+#[derive(Debug, Default)]
+struct Node {
+    exact: HashMap<Box<str>, Node>,
+    single: Option<Box<Node>>,
+    // the subscribers whose patterns end at this node
+    ending: Vec<Subscriber>,
+    // the subscribers whose patterns end with a multi-level wildcard here
+    trailing: Vec<Subscriber>,
+}
+
+impl Node {
+    /// Adds a subscriber under its pattern.
+    fn insert(&mut self, subscriber: Subscriber) {
+        let pattern = subscriber.pattern.clone();
+        let mut node = self;
+
+        for level in pattern.levels() {
+            node = match level {
+                | Level::Exact(level) => {
+                    node.exact.entry(level.into()).or_default()
+                }
+                | Level::Single => node.single.get_or_insert_with(Box::default),
+                | Level::Multi => return node.trailing.push(subscriber),
+            };
+        }
+
+        node.ending.push(subscriber);
+    }
+
+    /// Removes a subscriber from under its pattern's levels, pruning the nodes
+    /// left empty.
+    ///
+    /// # Returns
+    /// Whether this node was left empty or not.
+    fn remove(&mut self, levels: &[Level<'_>], id: u64) -> bool {
+        match levels.split_first() {
+            | None => self.ending.retain(|subscriber| subscriber.id != id),
+            | Some((Level::Multi, _)) => {
+                self.trailing.retain(|subscriber| subscriber.id != id)
+            }
+            | Some((Level::Single, rest)) => {
+                if let Some(ref mut child) = self.single {
+                    if child.remove(rest, id) {
+                        self.single = None;
+                    }
+                }
+            }
+            | Some((Level::Exact(level), rest)) => {
+                if let Some(child) = self.exact.get_mut(*level) {
+                    if child.remove(rest, id) {
+                        self.exact.remove(*level);
+                    }
+                }
+            }
+        }
+
+        self.exact.is_empty()
+            && self.single.is_none()
+            && self.ending.is_empty()
+            && self.trailing.is_empty()
+    }
+
+    /// Collects the subscribers whose patterns match the remaining levels of
+    /// a topic.
+    fn collect(&self, mut levels: Split<'_, char>, out: &mut Vec<Subscriber>) {
+        out.extend_from_slice(&self.trailing);
+
+        let Some(level) = levels.next() else {
+            return out.extend_from_slice(&self.ending);
+        };
+
+        if let Some(child) = self.exact.get(level) {
+            child.collect(levels.clone(), out);
+        }
+
+        if let Some(ref child) = self.single {
+            child.collect(levels, out);
+        }
+    }
+}
+
+/// Locks the trie of subscribers for reading, ignoring poisoning as the trie
+/// is never left in an inconsistent state.
+#[inline]
+fn read(subscribers: &RwLock<Node>) -> RwLockReadGuard<'_, Node> {
+    subscribers.read().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Locks the trie of subscribers for writing, ignoring poisoning as the trie
+/// is never left in an inconsistent state.
+#[inline]
+fn write(subscribers: &RwLock<Node>) -> RwLockWriteGuard<'_, Node> {
+    subscribers.write().unwrap_or_else(|err| err.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MuxError;
+
+    #[test]
+    fn pattern_test() {
+        let errors = [
+            ("", PatternError::EmptyLevel),
+            ("orders..created", PatternError::EmptyLevel),
+            ("orders.", PatternError::EmptyLevel),
+            ("orders.eu*", PatternError::PartialWildcard),
+            ("orders.#eu", PatternError::PartialWildcard),
+            ("orders.#.created", PatternError::MisplacedMultiLevel),
+        ];
+
+        for (pattern, err) in errors {
+            assert_eq!(Err(err), pattern.parse::<Pattern>());
+        }
+
+        let cases = [
+            ("orders.eu.created", "orders.eu.created", true),
+            ("orders.eu.created", "orders.eu", false),
+            ("orders.*.created", "orders.eu.created", true),
+            ("orders.*.created", "orders.created", false),
+            ("orders.*", "orders.eu.created", false),
+            ("orders.#", "orders", true),
+            ("orders.#", "orders.eu.created", true),
+            ("orders.#", "payments.eu", false),
+            ("*.*.#", "orders", false),
+            ("#", "orders.eu.created", true),
+        ];
+
+        for (pattern, topic, matches) in cases {
+            let pattern = Pattern::new(pattern).unwrap();
+
+            assert_eq!(matches, pattern.matches(topic), "{pattern} {topic}");
+        }
+    }
+
+    #[tokio::test]
+    async fn topic_bus_test() {
+        let topics = TopicBus::new(4);
+        let pattern = |pattern: &str| Pattern::new(pattern).unwrap();
+
+        let mut exact = topics.subscribe(pattern("orders.eu.created")).unwrap();
+        let mut single = topics.subscribe(pattern("orders.*.created")).unwrap();
+        let mut multi = topics.subscribe(pattern("orders.#")).unwrap();
+        let mut other = topics.subscribe(pattern("orders.#")).unwrap();
+
+        assert_ne!(multi.subscriber(), other.subscriber());
+
+        let report = topics.publish("orders.eu.created", 1).await;
+
+        assert_eq!(4, report.delivered.len());
+        assert!(report.is_complete());
+
+        let report = topics.publish("orders.us.shipped", 2).await;
+        let mut delivered: Vec<_> =
+            report.delivered.iter().map(Subscriber::id).collect();
+
+        delivered.sort_unstable();
+        assert_eq!(vec![multi.subscriber().id(), other.subscriber().id()], {
+            delivered
+        });
+
+        assert!(topics.publish("payments.eu", 3).await.delivered.is_empty());
+
+        for sub in [&mut exact, &mut single] {
+            assert_eq!(Some(("orders.eu.created".into(), 1)), sub.recv().await);
+        }
+
+        for sub in [&mut multi, &mut other] {
+            assert_eq!(Some(("orders.eu.created".into(), 1)), sub.recv().await);
+            assert_eq!(Some(("orders.us.shipped".into(), 2)), sub.recv().await);
+        }
+
+        // dropped subscriptions no longer match
+        drop((single, other));
+
+        assert_eq!(2, topics.matching("orders.eu.created").len());
+        assert_eq!(1, topics.matching("orders").len());
+
+        // closed lanes are reported as failed until unsubscribed
+        topics.bus().close(multi.subscriber());
+
+        let report = topics.publish("orders.eu.created", 4).await;
+
+        assert_eq!(vec![exact.subscriber().clone()], report.delivered);
+        assert!(matches!(
+            &report.failed[..],
+            [MuxError::Rejected(subscriber, _)] if subscriber == multi.subscriber()
+        ));
+
+        drop((exact, multi));
+
+        // the trie is pruned down to its root
+        assert!(write(&topics.subscribers).remove(&[], u64::MAX));
+    }
+}
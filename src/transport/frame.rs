@@ -0,0 +1,219 @@
+use std::io;
+
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::codec::{get_varint, invalid_data, put_varint, Codec, FrameCodec};
+
+/// The lane or connection was closed without an error.
+pub const NO_ERROR: u32 = 0;
+/// The remote end violated the protocol.
+pub const PROTOCOL_ERROR: u32 = 1;
+/// The lane was refused by the remote end, and no data was processed.
+pub const REFUSED: u32 = 2;
+/// The lane was cancelled because its receiver was closed.
+pub const CANCEL: u32 = 3;
+
+const DATA: u8 = 0;
+const OPEN: u8 = 1;
+const FIN: u8 = 2;
+const RST: u8 = 3;
+const GOAWAY: u8 = 4;
+const WINDOW_UPDATE: u8 = 5;
+const PING: u8 = 6;
+const PONG: u8 = 7;
+
+/// A single frame of the wire protocol spoken by
+/// [`Transport`](crate::transport::Transport).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame<T, V> {
+    /// A value sent through the lane identified with the tag.
+    Data(T, V),
+    /// Opens a new lane. Lanes can also be opened implicitly by sending data
+    /// on them.
+    Open(T),
+    /// Half-closes a lane, the sender will not send any more data on it.
+    Fin(T),
+    /// Abruptly closes a lane in both directions, with an error code.
+    Reset(T, u32),
+    /// Tells the remote end that no more lanes will be accepted, with an
+    /// error code.
+    GoAway(u32),
+    /// Grants the remote end credit to send the given number of additional
+    /// values through the lane.
+    WindowUpdate(T, u32),
+    /// Asks the remote end to answer with a [`Frame::Pong`] carrying the
+    /// same opaque value.
+    Ping(u32),
+    /// Answers a [`Frame::Ping`] from the remote end.
+    Pong(u32),
+}
+
+/// A [`Decoder`] and [`Encoder`] of [`Frame`]s, that encodes tags with `TC`
+/// and values with `VC`.
+///
+/// Frames share the layout of [`FrameCodec`], with the body prefixed by a
+/// single byte identifying the frame type.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WireCodec<TC, VC>(FrameCodec<TC, VC>);
+
+impl<TC, VC> WireCodec<TC, VC> {
+    /// Creates a new instance of [`WireCodec`].
+    ///
+    /// # Parameters
+    /// * `tag` - The codec used to encode and decode tags.
+    /// * `value` - The codec used to encode and decode values.
+    #[inline]
+    pub const fn new(tag: TC, value: VC) -> Self {
+        Self(FrameCodec::new(tag, value))
+    }
+}
+
+impl<TC, VC> From<FrameCodec<TC, VC>> for WireCodec<TC, VC> {
+    #[inline]
+    fn from(codec: FrameCodec<TC, VC>) -> Self {
+        Self(codec)
+    }
+}
+
+impl<TC, VC> Encoder<Frame<TC::Item, VC::Item>> for WireCodec<TC, VC>
+where
+    TC: Codec,
+    VC: Codec,
+{
+    type Error = io::Error;
+
+    fn encode(
+        &mut self,
+        frame: Frame<TC::Item, VC::Item>,
+        dst: &mut BytesMut,
+    ) -> io::Result<()> {
+        self.0.encode_frame(dst, |tc, vc, body| match frame {
+            | Frame::Data(tag, value) => {
+                body.put_u8(DATA);
+                tc.encode(tag, body)?;
+                vc.encode(value, body)
+            }
+            | Frame::Open(tag) => {
+                body.put_u8(OPEN);
+                tc.encode(tag, body)
+            }
+            | Frame::Fin(tag) => {
+                body.put_u8(FIN);
+                tc.encode(tag, body)
+            }
+            | Frame::Reset(tag, code) => {
+                body.put_u8(RST);
+                tc.encode(tag, body)?;
+                put_varint(body, code.into());
+                Ok(())
+            }
+            | Frame::GoAway(code) => {
+                body.put_u8(GOAWAY);
+                put_varint(body, code.into());
+                Ok(())
+            }
+            | Frame::WindowUpdate(tag, credit) => {
+                body.put_u8(WINDOW_UPDATE);
+                tc.encode(tag, body)?;
+                put_varint(body, credit.into());
+                Ok(())
+            }
+            | Frame::Ping(opaque) => {
+                body.put_u8(PING);
+                put_varint(body, opaque.into());
+                Ok(())
+            }
+            | Frame::Pong(opaque) => {
+                body.put_u8(PONG);
+                put_varint(body, opaque.into());
+                Ok(())
+            }
+        })
+    }
+}
+
+impl<TC, VC> Decoder for WireCodec<TC, VC>
+where
+    TC: Codec,
+    VC: Codec,
+{
+    type Error = io::Error;
+    type Item = Frame<TC::Item, VC::Item>;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Self::Item>> {
+        let Some(mut body) = self.0.decode_frame(src)? else {
+            return Ok(None);
+        };
+
+        if !body.has_remaining() {
+            return Err(invalid_data("empty frame"));
+        }
+
+        let (tc, vc) = self.0.codecs_mut();
+        let frame = match body.get_u8() {
+            | DATA => Frame::Data(tc.decode(&mut body)?, vc.decode(&mut body)?),
+            | OPEN => Frame::Open(tc.decode(&mut body)?),
+            | FIN => Frame::Fin(tc.decode(&mut body)?),
+            | RST => Frame::Reset(tc.decode(&mut body)?, get_u32(&mut body)?),
+            | GOAWAY => Frame::GoAway(get_u32(&mut body)?),
+            | WINDOW_UPDATE => {
+                Frame::WindowUpdate(tc.decode(&mut body)?, get_u32(&mut body)?)
+            }
+            | PING => Frame::Ping(get_u32(&mut body)?),
+            | PONG => Frame::Pong(get_u32(&mut body)?),
+            | ty => {
+                return Err(invalid_data(format!("unknown frame type {ty}")))
+            }
+        };
+
+        if !body.is_empty() {
+            return Err(invalid_data("trailing bytes in frame"));
+        }
+
+        Ok(Some(frame))
+    }
+}
+
+#[inline]
+fn get_u32(src: &mut BytesMut) -> io::Result<u32> {
+    u32::try_from(get_varint(src)?).map_err(invalid_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::codec::{BytesCodec, VarintCodec};
+
+    #[test]
+    fn wire_codec_test() {
+        let mut codec = WireCodec::new(VarintCodec::<u64>::new(), BytesCodec);
+        let mut buf = BytesMut::new();
+
+        let frames = [
+            Frame::Open(7),
+            Frame::Data(7, Bytes::from_static(b"hello")),
+            Frame::WindowUpdate(7, u32::MAX),
+            Frame::Fin(7),
+            Frame::Reset(u64::MAX, CANCEL),
+            Frame::GoAway(PROTOCOL_ERROR),
+            Frame::Ping(42),
+            Frame::Pong(42),
+        ];
+
+        for frame in frames.clone() {
+            codec.encode(frame, &mut buf).unwrap();
+        }
+
+        for frame in frames {
+            assert_eq!(Some(frame), codec.decode(&mut buf).unwrap());
+        }
+
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+
+        buf.extend_from_slice(&[1, 0xFF]);
+        assert!(codec.decode(&mut buf).is_err());
+    }
+}
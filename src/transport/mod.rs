@@ -0,0 +1,940 @@
+//! Running a [`Mux`](crate::mux::Mux) over a byte-oriented transport.
+//!
+//! Besides lane data, the wire protocol carries lane lifecycle control frames
+//! (see [`Frame`]), so that opening, half-closing and resetting a lane is
+//! reflected on the remote end.
+
+mod frame;
+mod state;
+#[cfg(feature = "yamux")]
+pub mod yamux;
+
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::mpsc;
+use tokio_util::codec::{Decoder, Encoder, Framed};
+
+pub use self::frame::{
+    Frame,
+    WireCodec,
+    CANCEL,
+    NO_ERROR,
+    PROTOCOL_ERROR,
+    REFUSED,
+};
+use self::state::{Inbound, State};
+use crate::lane::{CloseReason, Hooks, Signal};
+use crate::{
+    AtCapacity,
+    Delivery,
+    Incoming,
+    Key,
+    Lane,
+    Mux,
+    MuxError,
+    OpenError,
+    Outgoing,
+    TagAllocator,
+};
+
+/// How often a running [`Transport`] closes lanes that were idle for their
+/// idle timeout (see [`Transport::with_idle_timeout`]).
+pub const IDLE_SWEEP: Duration = Duration::from_secs(1);
+
+/// A driver that runs a [`Mux`](crate::mux::Mux) over a byte-oriented
+/// transport.
+///
+/// Inbound frames are demultiplexed into lanes, and outgoing messages from all
+/// lanes are written back to the transport. The transport is only driven
+/// while [`Transport::run`] is being polled.
+///
+/// Lane lifecycle is reflected on the remote end as follows:
+/// * A lane that is new to the remote end is opened with [`Frame::Open`] before
+///   its first value is written.
+/// * Dropping all [`LaneTx`](crate::lane::LaneTx) handles of a lane half-closes
+///   it with [`Frame::Fin`].
+/// * Closing or dropping a [`LaneRx`](crate::lane::LaneRx) before the remote
+///   end half-closed the lane resets it with [`Frame::Reset`].
+/// * Dropping the [`Incoming`] receiver stops accepting new lanes, which sends
+///   [`Frame::GoAway`] and refuses any further lanes with [`REFUSED`]. Lanes
+///   the remote end opens while the backlog of [`Incoming`] is full are refused
+///   with [`REFUSED`] as well.
+///
+/// Each lane is flow controlled separately: a lane can only have `lane_buf`
+/// values in flight before its [`LaneTx`](crate::lane::LaneTx) waits for the
+/// remote end to grant more credit with [`Frame::WindowUpdate`], which happens
+/// as the remote [`LaneRx`](crate::lane::LaneRx) consumes them. A slow
+/// consumer thus only stalls its own lane. Both ends must use the same
+/// `lane_buf`, as a remote end that sends past the credit it was granted
+/// fills the lane, which is then reset with [`PROTOCOL_ERROR`].
+#[derive(Debug)]
+pub struct Transport<T: Key, V, IO, C> {
+    mux: Arc<Mux<T, V>>,
+    hooks: Hooks<T, V>,
+    rx: Outgoing<T, V>,
+    signals: mpsc::UnboundedReceiver<Signal<T>>,
+    io: Framed<IO, C>,
+    ack: bool,
+}
+
+impl<T, V, IO, C> Transport<T, V, IO, C>
+where
+    T: Key,
+    IO: AsyncRead + AsyncWrite + Unpin,
+    C: Decoder<Item = Frame<T, V>, Error = io::Error>
+        + Encoder<Frame<T, V>, Error = io::Error>,
+{
+    /// Gets a handle to the transport's mux, which can be used to open lanes
+    /// locally with [`Mux::open`](crate::mux::Mux::open) or
+    /// [`Mux::open_with`](crate::mux::Mux::open_with).
+    ///
+    /// Lanes opened locally are announced to the remote end with
+    /// [`Frame::Open`] once their first value is written.
+    #[inline]
+    pub fn mux(&self) -> Arc<Mux<T, V>> {
+        self.mux.clone()
+    }
+
+    /// Sets the allocator of tags for lanes opened with
+    /// [`Mux::open`](crate::mux::Mux::open).
+    ///
+    /// To avoid collisions, both ends must allocate disjoint tags, e.g. with
+    /// [`Parity::odd`](crate::tag::Parity) on one end and
+    /// [`Parity::even`](crate::tag::Parity) on the other.
+    ///
+    /// # Parameters
+    /// * `allocator` - The tag allocator.
+    #[inline]
+    pub fn with_allocator<A>(self, allocator: A) -> Self
+    where
+        A: TagAllocator<T> + 'static,
+    {
+        self.mux.set_allocator(allocator);
+        self
+    }
+
+    /// Closes lanes that see no traffic in either direction for `timeout`,
+    /// resetting them on the remote end with [`CANCEL`].
+    ///
+    /// See [`Mux::with_idle_timeout`](crate::mux::Mux::with_idle_timeout).
+    /// Lanes whose receivers are left unattended are closed every
+    /// [`IDLE_SWEEP`].
+    ///
+    /// # Parameters
+    /// * `timeout` - The idle timeout.
+    #[inline]
+    pub fn with_idle_timeout(self, timeout: Duration) -> Self {
+        self.mux.bus().set_idle_timeout(Some(timeout));
+        self
+    }
+
+    /// Sets the maximum number of lanes that can be open at once, so that the
+    /// remote end cannot exhaust memory by sending to many distinct tags.
+    ///
+    /// Lanes the remote end opens over the limit are reset with [`REFUSED`],
+    /// unless `at_capacity` evicts the least recently used idle lane instead,
+    /// in which case that lane is reset with [`CANCEL`].
+    ///
+    /// See [`Mux::with_max_lanes`](crate::mux::Mux::with_max_lanes).
+    ///
+    /// # Parameters
+    /// * `max` - The maximum number of lanes.
+    /// * `at_capacity` - What to do with new lanes over the limit.
+    #[inline]
+    pub fn with_max_lanes(self, max: usize, at_capacity: AtCapacity) -> Self {
+        self.mux.bus().set_max_lanes(max, at_capacity);
+        self
+    }
+
+    /// Limits how fast new lanes can be created, resetting lanes the remote
+    /// end opens over the rate with [`REFUSED`].
+    ///
+    /// See [`Mux::with_lane_rate`](crate::mux::Mux::with_lane_rate).
+    ///
+    /// # Parameters
+    /// * `lanes` - The number of lanes that can be created in a burst.
+    /// * `per` - How long the burst takes to replenish.
+    #[inline]
+    pub fn with_lane_rate(self, lanes: u32, per: Duration) -> Self {
+        self.mux.bus().set_lane_rate(lanes, per);
+        self
+    }
+
+    /// Drives the transport until the remote end closes it, or an I/O error
+    /// occurs.
+    ///
+    /// Once this returns, all lanes are closed. If the remote end sent an
+    /// invalid frame, a [`Frame::GoAway`] with [`PROTOCOL_ERROR`] is sent
+    /// before returning.
+    ///
+    /// # Returns
+    /// * [`Ok(())`] - If the transport was closed by the remote end, even if
+    ///   there were still frames to write.
+    /// * [`Err(io::Error)`] - If reading, decoding, encoding or writing a frame
+    ///   failed.
+    pub async fn run(self) -> io::Result<()> {
+        let Self {
+            mux,
+            hooks,
+            mut rx,
+            mut signals,
+            io,
+            ack,
+        } = self;
+        let (mut sink, mut stream) = io.split();
+        let (ctl_tx, mut ctl_rx) = mpsc::unbounded_channel();
+        let state = State::new(hooks.window());
+        let mut sweep = tokio::time::interval(IDLE_SWEEP);
+
+        // reading and writing are driven concurrently, so that a full lane
+        // never prevents lanes' outgoing messages from being written
+        let read = async {
+            loop {
+                tokio::select! {
+                    frame = stream.next() => match frame.transpose()? {
+                        | Some(frame) => {
+                            let ctl = &ctl_tx;
+                            Self::on_frame(&mux, &hooks, &state, ctl, ack, frame)
+                        }
+                        | None => return Ok(()),
+                    },
+                    _ = mux.refusing(), if !State::lock(&state).is_refusing() => {
+                        // nobody is accepting lanes anymore
+                        if State::lock(&state).refuse() {
+                            _ = ctl_tx.send(Frame::GoAway(NO_ERROR));
+                        }
+                    }
+                    _ = sweep.tick() => {
+                        mux.evict_idle();
+                    }
+                }
+            }
+        };
+
+        let write = async {
+            loop {
+                tokio::select! {
+                    biased;
+
+                    Some(frame) = ctl_rx.recv() => sink.send(frame).await?,
+                    Some((tag, value)) = rx.recv() => {
+                        Self::on_outgoing(&mux, &hooks, &state, &mut sink, tag, value)
+                            .await?;
+                        sink.flush().await?;
+                    }
+                    Some(signal) = signals.recv() => {
+                        // values sent before the signal was emitted go first
+                        while let Ok((tag, value)) = rx.try_recv() {
+                            Self::on_outgoing(&mux, &hooks, &state, &mut sink, tag, value)
+                                .await?;
+                        }
+
+                        // a replaced lane must not affect the one that
+                        // replaced it
+                        let frame = if hooks.is_stale(&signal) {
+                            None
+                        } else {
+                            State::lock(&state).signal(signal)
+                        };
+
+                        if let Some(Frame::Reset(ref tag, _)) = frame {
+                            // the lane was reset locally, stop sending on it
+                            hooks.revoke(tag);
+                        }
+
+                        if let Some(frame) = frame {
+                            sink.feed(frame).await?;
+                        }
+
+                        sink.flush().await?;
+                    }
+                    else => return Ok(()),
+                }
+            }
+        };
+
+        let res: io::Result<()> = tokio::select! {
+            res = read => res,
+            res = write => res,
+        };
+
+        // the mux might outlive the transport, so lanes that are still open
+        // are closed explicitly
+        let err = match res {
+            | Ok(()) => {
+                io::Error::new(io::ErrorKind::UnexpectedEof, "transport closed")
+            }
+            | Err(ref err) => io::Error::new(err.kind(), err.to_string()),
+        };
+
+        mux.bus().clear_with(CloseReason::Transport(Arc::new(err)));
+
+        match res {
+            | Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                _ = sink.send(Frame::GoAway(PROTOCOL_ERROR)).await;
+                Err(err)
+            }
+            // the remote end closed the transport while we were writing
+            | Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            | res => res,
+        }
+    }
+
+    /// Handles an inbound frame without waiting, so that a full lane or
+    /// backlog never stops frames from being read for the other lanes.
+    fn on_frame(
+        mux: &Mux<T, V>,
+        hooks: &Hooks<T, V>,
+        state: &Mutex<State<T>>,
+        ctl: &mpsc::UnboundedSender<Frame<T, V>>,
+        ack: bool,
+        frame: Frame<T, V>,
+    ) {
+        match frame {
+            | Frame::Data(tag, value) => {
+                let cost = hooks.cost(&value);
+                let inbound = State::lock(state).inbound(&tag, cost);
+
+                match inbound {
+                    | Inbound::Existing => {
+                        // a lane holds a window's worth of values, so it is
+                        // never full unless its overflow policy was changed,
+                        // and it might have been closed locally
+                        let pushed = mux.bus().try_push_existing(tag, value);
+
+                        if let Err(MuxError::Full(tag, _)) = pushed {
+                            Self::overrun(mux, hooks, state, ctl, tag);
+                        }
+                    }
+                    | Inbound::New => match mux.try_deliver(tag, value) {
+                        | Ok(Delivery::New(lane)) => {
+                            Self::refuse(state, ctl, lane);
+                        }
+                        | Err(MuxError::Refused(tag, _)) => {
+                            State::lock(state).forget(&tag);
+                            _ = ctl.send(Frame::Reset(tag, REFUSED));
+                        }
+                        | _ => {}
+                    },
+                    | Inbound::Refused => {
+                        _ = ctl.send(Frame::Reset(tag, REFUSED));
+                    }
+                    // the remote end sent past the credit it was granted
+                    | Inbound::Overrun => {
+                        Self::overrun(mux, hooks, state, ctl, tag);
+                    }
+                    | Inbound::Stale => {}
+                }
+            }
+            | Frame::Open(tag) => {
+                let inbound = State::lock(state).inbound(&tag, 0);
+
+                match inbound {
+                    | Inbound::New => match mux.open_with(tag.clone()) {
+                        // the lane is only acknowledged once accepted
+                        | Ok(lane) => match mux.try_offer(lane) {
+                            | Some(lane) => Self::refuse(state, ctl, lane),
+                            | None if ack => _ = ctl.send(Frame::Open(tag)),
+                            | None => {}
+                        },
+                        | Err(OpenError::Refused(tag)) => {
+                            State::lock(state).forget(&tag);
+                            _ = ctl.send(Frame::Reset(tag, REFUSED));
+                        }
+                        // the tag is taken by a lane opened locally
+                        | Err(OpenError::InUse(tag)) => {
+                            State::lock(state).forget(&tag);
+                            _ = ctl.send(Frame::Reset(tag, PROTOCOL_ERROR));
+                        }
+                        // no tag is allocated for lanes opened remotely
+                        | Err(OpenError::Exhausted) => {}
+                    },
+                    | Inbound::Refused => {
+                        _ = ctl.send(Frame::Reset(tag, REFUSED));
+                    }
+                    | Inbound::Existing | Inbound::Stale | Inbound::Overrun => {
+                    }
+                }
+            }
+            | Frame::Fin(tag) => {
+                State::lock(state).remote_fin(&tag);
+                mux.bus().close(&tag);
+            }
+            | Frame::Reset(tag, code) => {
+                State::lock(state).reset(&tag);
+                hooks.revoke(&tag);
+                mux.bus().close_with(&tag, CloseReason::Reset(code));
+            }
+            | Frame::GoAway(_) => State::lock(state).remote_go_away(),
+            | Frame::WindowUpdate(tag, credit) => hooks.grant(&tag, credit),
+            | Frame::Ping(opaque) => _ = ctl.send(Frame::Pong(opaque)),
+            | Frame::Pong(_) => {}
+        }
+    }
+
+    /// Resets a lane with [`PROTOCOL_ERROR`], as the remote end sent past the
+    /// credit it was granted for it.
+    fn overrun(
+        mux: &Mux<T, V>,
+        hooks: &Hooks<T, V>,
+        state: &Mutex<State<T>>,
+        ctl: &mpsc::UnboundedSender<Frame<T, V>>,
+        tag: T,
+    ) {
+        State::lock(state).reset(&tag);
+        hooks.revoke(&tag);
+        mux.bus()
+            .close_with(&tag, CloseReason::Reset(PROTOCOL_ERROR));
+        _ = ctl.send(Frame::Reset(tag, PROTOCOL_ERROR));
+    }
+
+    /// Resets a new lane with [`REFUSED`], as nobody is accepting lanes or
+    /// the backlog of [`Incoming`] is full.
+    fn refuse(
+        state: &Mutex<State<T>>,
+        ctl: &mpsc::UnboundedSender<Frame<T, V>>,
+        mut lane: Lane<T, V>,
+    ) {
+        let tag = lane.receiver().tag().clone();
+
+        // the lane is reset already once it is dropped
+        State::lock(state).reset(&tag);
+        drop(lane);
+        _ = ctl.send(Frame::Reset(tag, REFUSED));
+    }
+
+    async fn on_outgoing<S>(
+        mux: &Mux<T, V>,
+        hooks: &Hooks<T, V>,
+        state: &Mutex<State<T>>,
+        sink: &mut S,
+        tag: T,
+        value: V,
+    ) -> io::Result<()>
+    where
+        S: futures::Sink<Frame<T, V>, Error = io::Error> + Unpin,
+    {
+        let frames = State::lock(state).outgoing(tag.clone(), value);
+
+        match frames {
+            | Some(frames) => {
+                for frame in frames {
+                    sink.feed(frame).await?;
+                }
+            }
+            | None => {
+                // the remote end went away before the lane was opened
+                hooks.revoke(&tag);
+                mux.bus().close_with(&tag, CloseReason::Reset(REFUSED));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Key, V> Mux<T, V> {
+    /// Creates a new [`Mux`](crate::mux::Mux) that runs over `io`, using
+    /// `codec` to frame tagged values.
+    ///
+    /// This is only available when the `transport` feature is enabled.
+    ///
+    /// # Parameters
+    /// * `io` - The underlying byte stream.
+    /// * `codec` - The codec used to encode and decode [`Frame`]s.
+    /// * `buf` - The buffer size of each lane's outgoing messages queue.
+    /// * `lane_buf` - The buffer size of each lane.
+    ///
+    /// # Returns
+    /// A [`Transport`] that must be driven for any I/O to happen, and a
+    /// receiver that yields lanes as they are created by the remote end.
+    pub fn over<IO, C>(
+        io: IO,
+        codec: C,
+        buf: usize,
+        lane_buf: usize,
+    ) -> (Transport<T, V, IO, C>, Incoming<T, V>)
+    where
+        IO: AsyncRead + AsyncWrite + Unpin,
+        C: Decoder<Item = Frame<T, V>, Error = io::Error>
+            + Encoder<Frame<T, V>, Error = io::Error>,
+    {
+        let (signals_tx, signals) = mpsc::unbounded_channel();
+        let window = u32::try_from(lane_buf).unwrap_or(u32::MAX);
+
+        Transport::new(
+            io,
+            codec,
+            buf,
+            lane_buf,
+            lane_buf,
+            Hooks::new(signals_tx, window),
+            signals,
+            false,
+        )
+    }
+}
+
+impl<T, V, IO, C> Transport<T, V, IO, C>
+where
+    T: Key,
+    IO: AsyncRead + AsyncWrite + Unpin,
+    C: Decoder<Item = Frame<T, V>, Error = io::Error>
+        + Encoder<Frame<T, V>, Error = io::Error>,
+{
+    /// Creates a new instance of [`Transport`], along with its mux.
+    ///
+    /// # Parameters
+    /// * `io` - The underlying byte stream.
+    /// * `codec` - The codec used to encode and decode [`Frame`]s.
+    /// * `buf` - The buffer size of each lane's outgoing messages queue.
+    /// * `lane_buf` - The buffer size of each lane, which must hold at least a
+    ///   window's worth of values.
+    /// * `backlog` - The number of lanes opened by the remote end that can wait
+    ///   to be accepted.
+    /// * `hooks` - The hooks controlling the lanes.
+    /// * `signals` - The receiver of the signals emitted through `hooks`.
+    /// * `ack` - Whether lanes opened by the remote end are acknowledged by
+    ///   answering with [`Frame::Open`].
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        io: IO,
+        codec: C,
+        buf: usize,
+        lane_buf: usize,
+        backlog: usize,
+        hooks: Hooks<T, V>,
+        signals: mpsc::UnboundedReceiver<Signal<T>>,
+        ack: bool,
+    ) -> (Self, Incoming<T, V>) {
+        let (mut mux, rx) = Mux::with_hooks(buf, lane_buf, hooks.clone());
+        let incoming = mux.incoming(backlog);
+        let transport = Self {
+            mux: Arc::new(mux),
+            hooks,
+            rx,
+            signals,
+            io: Framed::new(io, codec),
+            ack,
+        };
+
+        (transport, incoming)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::io::AsyncWriteExt;
+    use tokio::sync::mpsc::error::TrySendError;
+    use tokio::time::timeout;
+
+    use super::*;
+    use crate::codec::{StrCodec, VarintCodec};
+
+    type TestCodec = WireCodec<VarintCodec<u32>, StrCodec>;
+
+    #[tokio::test]
+    async fn transport_echo_test() {
+        let lane_cnt: u32 = 32;
+        let msg_cnt: u32 = 64;
+
+        let (client, server) = tokio::io::duplex(64);
+        let (transport, mut lanes) =
+            Mux::over(server, TestCodec::default(), 8, 4);
+        let driver = tokio::spawn(transport.run());
+
+        tokio::spawn(async move {
+            while let Some(lane) = lanes.accept().await {
+                tokio::spawn(async move {
+                    let (mut tx, mut rx) = lane.split();
+
+                    while let Some(value) = rx.recv().await {
+                        tx.send(value.to_uppercase()).await.unwrap();
+                    }
+                });
+            }
+        });
+
+        let mut client = Framed::new(client, TestCodec::default());
+
+        for msg_no in 0..msg_cnt {
+            for tag in 0..lane_cnt {
+                let value = format!("lane-{tag}-msg-{msg_no}");
+
+                client.feed(Frame::Data(tag, value)).await.unwrap();
+            }
+
+            client.flush().await.unwrap();
+
+            let mut echoed = 0;
+
+            while echoed < lane_cnt {
+                match client.next().await.unwrap().unwrap() {
+                    | Frame::Data(tag, value) => {
+                        assert_eq!(format!("LANE-{tag}-MSG-{msg_no}"), value);
+
+                        // grant credit back for every received value
+                        client.send(Frame::WindowUpdate(tag, 1)).await.unwrap();
+                        echoed += 1;
+                    }
+                    | Frame::WindowUpdate(..) => {}
+                    | frame => panic!("unexpected frame: {frame:?}"),
+                }
+            }
+        }
+
+        drop(client);
+
+        driver.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn transport_flow_control_test() {
+        let (client, server) = tokio::io::duplex(64);
+        let (transport, mut lanes) =
+            Mux::over(server, TestCodec::default(), 8, 4);
+        let driver = tokio::spawn(transport.run());
+        let mut client = Framed::new(client, TestCodec::default());
+
+        let handler = tokio::spawn(async move {
+            let (mut tx, mut rx) = lanes.accept().await.unwrap().split();
+
+            assert_eq!(Some("go".to_owned()), rx.recv().await);
+
+            for i in 0..10 {
+                tx.send(i.to_string()).await.unwrap();
+            }
+
+            assert_eq!(Some("x".to_owned()), rx.recv().await);
+
+            (lanes, tx, rx)
+        });
+
+        client.send(Frame::Data(1, "go".to_owned())).await.unwrap();
+
+        // only a window's worth of values are sent without credit
+        for i in 0..4 {
+            let frame = client.next().await.unwrap().unwrap();
+
+            assert_eq!(Frame::Data(1, i.to_string()), frame);
+        }
+
+        let pending = Duration::from_millis(50);
+
+        assert!(timeout(pending, client.next()).await.is_err());
+
+        client.send(Frame::WindowUpdate(1, 6)).await.unwrap();
+
+        for i in 4..10 {
+            let frame = client.next().await.unwrap().unwrap();
+
+            assert_eq!(Frame::Data(1, i.to_string()), frame);
+        }
+
+        // credit is granted back once half of the window is consumed
+        client.send(Frame::Data(1, "x".to_owned())).await.unwrap();
+
+        assert_eq!(
+            Frame::WindowUpdate(1, 2),
+            client.next().await.unwrap().unwrap()
+        );
+
+        drop(client);
+        driver.await.unwrap().unwrap();
+        drop(handler.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn transport_window_test() {
+        let (client, server) = tokio::io::duplex(64);
+        let (transport, mut lanes) =
+            Mux::over(server, TestCodec::default(), 8, 2);
+        let driver = tokio::spawn(transport.run());
+        let mut client = Framed::new(client, TestCodec::default());
+
+        // a lane whose values are never received
+        for value in ["a", "b"] {
+            client.send(Frame::Data(1, value.to_owned())).await.unwrap();
+        }
+
+        let (_tx, mut slow) = lanes.accept().await.unwrap().split();
+
+        // sending past the window resets the lane
+        client.send(Frame::Data(1, "c".to_owned())).await.unwrap();
+        assert_eq!(
+            Frame::Reset(1, PROTOCOL_ERROR),
+            client.next().await.unwrap().unwrap()
+        );
+
+        // without holding up the other lanes
+        client.send(Frame::Data(3, "d".to_owned())).await.unwrap();
+
+        let (_tx, mut other) = lanes.accept().await.unwrap().split();
+
+        // new lanes are refused while the backlog is full
+        for tag in [5, 7, 9] {
+            client.send(Frame::Data(tag, "e".to_owned())).await.unwrap();
+        }
+
+        assert_eq!(
+            Frame::Reset(9, REFUSED),
+            client.next().await.unwrap().unwrap()
+        );
+
+        assert_eq!(Some("d".to_owned()), other.recv().await);
+
+        // values received before the reset are kept
+        assert_eq!(Some("a".to_owned()), slow.recv().await);
+        assert_eq!(Some("b".to_owned()), slow.recv().await);
+        assert!(matches!(
+            slow.recv_result().await,
+            Err(CloseReason::Reset(PROTOCOL_ERROR))
+        ));
+
+        drop(client);
+        driver.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn transport_cancelled_send_test() {
+        let (signals, _signals_rx) = mpsc::unbounded_channel();
+        let (mux, mut rx) = Mux::with_hooks(1, 4, Hooks::new(signals, 2));
+        let (mut tx, _rx) = mux.open_with(1).unwrap().split();
+        let pending = Duration::from_millis(50);
+
+        tx.send(0).await.unwrap();
+
+        // a send blocked on a full lane gives its credit back once cancelled
+        assert!(timeout(pending, tx.send(1)).await.is_err());
+        assert_eq!(Some((1, 0)), rx.recv().await);
+
+        timeout(pending, tx.send(2)).await.unwrap().unwrap();
+        assert_eq!(Some((1, 2)), rx.recv().await);
+
+        // until the window is spent
+        assert!(timeout(pending, tx.send(3)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn transport_reused_tag_test() {
+        let (signals, mut signals_rx) = mpsc::unbounded_channel();
+        let hooks = Hooks::new(signals, 1);
+        let (mux, _rx) = Mux::with_hooks(8, 4, hooks.clone());
+        let (mut old, old_rx) = mux.open_with(1).unwrap().split();
+
+        old.try_send(0).unwrap();
+
+        // the lane is reset, while its sender is still around
+        hooks.revoke(&1);
+        drop(old_rx);
+
+        assert!(old.is_closed());
+        assert!(matches!(signals_rx.try_recv(), Ok(Signal::RxClosed(1))));
+
+        // a new lane with the same tag gets its own credit
+        let (mut new, _new_rx) = mux.open_with(1).unwrap().split();
+
+        assert!(!new.is_closed());
+        new.try_send(1).unwrap();
+        assert!(matches!(new.try_send(2), Err(TrySendError::Full(_))));
+
+        // which the old sender does not take along once dropped
+        drop(old);
+        assert!(signals_rx.try_recv().is_err());
+
+        hooks.grant(&1, 1);
+        new.try_send(2).unwrap();
+
+        drop(new);
+        assert!(matches!(
+            signals_rx.try_recv(),
+            Ok(ref signal @ Signal::TxClosed(1, _)) if !hooks.is_stale(signal)
+        ));
+    }
+
+    #[tokio::test]
+    async fn transport_open_test() {
+        let (client, server) = tokio::io::duplex(64);
+        let (transport, _lanes) = Mux::over(server, TestCodec::default(), 8, 4);
+        let transport = transport.with_allocator(crate::Parity::<u32>::even());
+        let mux = transport.mux();
+        let driver = tokio::spawn(transport.run());
+        let mut client = Framed::new(client, TestCodec::default());
+
+        let (mut tx, mut rx) = mux.open().unwrap().split();
+
+        assert_eq!(&2, tx.tag());
+        assert!(mux.open_with(2).is_err());
+
+        // the lane is announced along with its first value
+        tx.send("hi".to_owned()).await.unwrap();
+
+        assert_eq!(Frame::Open(2), client.next().await.unwrap().unwrap());
+        assert_eq!(
+            Frame::Data(2, "hi".to_owned()),
+            client.next().await.unwrap().unwrap()
+        );
+
+        client.send(Frame::Data(2, "yo".to_owned())).await.unwrap();
+        assert_eq!(Some("yo".to_owned()), rx.recv().await);
+
+        // the remote end cannot open a lane whose tag is taken locally
+        let _taken = mux.open_with(4).unwrap();
+
+        client.send(Frame::Open(4)).await.unwrap();
+        assert_eq!(
+            Frame::Reset(4, PROTOCOL_ERROR),
+            client.next().await.unwrap().unwrap()
+        );
+
+        // lanes are closed once the transport is
+        drop(client);
+        driver.await.unwrap().unwrap();
+
+        assert_eq!(None, rx.recv().await);
+    }
+
+    #[tokio::test]
+    async fn transport_close_reason_test() {
+        let (client, server) = tokio::io::duplex(64);
+        let (transport, mut lanes) =
+            Mux::over(server, TestCodec::default(), 8, 4);
+        let driver = tokio::spawn(transport.run());
+        let mut client = Framed::new(client, TestCodec::default());
+
+        for tag in 1..=3 {
+            client.send(Frame::Open(tag)).await.unwrap();
+        }
+
+        let mut fin = lanes.accept().await.unwrap();
+        let mut reset = lanes.accept().await.unwrap();
+        let mut failed = lanes.accept().await.unwrap();
+
+        client.send(Frame::Fin(1)).await.unwrap();
+        client.send(Frame::Reset(2, 42)).await.unwrap();
+
+        assert!(matches!(
+            fin.receiver().recv_result().await,
+            Err(CloseReason::Graceful)
+        ));
+        assert!(matches!(
+            reset.receiver().recv_result().await,
+            Err(CloseReason::Reset(42))
+        ));
+
+        // lanes that are still open when the transport dies learn why
+        client.get_mut().write_all(&[1, 0xFF]).await.unwrap();
+
+        assert!(driver.await.unwrap().is_err());
+        assert!(matches!(
+            failed.receiver().recv_result().await,
+            Err(CloseReason::Transport(err)) if err.kind() == io::ErrorKind::InvalidData
+        ));
+    }
+
+    #[tokio::test]
+    async fn transport_admission_test() {
+        let (client, server) = tokio::io::duplex(64);
+        let (transport, mut lanes) =
+            Mux::over(server, TestCodec::default(), 8, 4);
+        let transport = transport.with_max_lanes(1, AtCapacity::Reject);
+        let _driver = tokio::spawn(transport.run());
+        let mut client = Framed::new(client, TestCodec::default());
+
+        client.send(Frame::Data(1, "a".to_owned())).await.unwrap();
+
+        let mut first = lanes.accept().await.unwrap();
+
+        assert_eq!(Some("a".to_owned()), first.receiver().recv().await);
+
+        // lanes over the limit are refused, however they are opened
+        client.send(Frame::Data(3, "b".to_owned())).await.unwrap();
+        client.send(Frame::Open(5)).await.unwrap();
+
+        assert_eq!(
+            Frame::Reset(3, REFUSED),
+            client.next().await.unwrap().unwrap()
+        );
+        assert_eq!(
+            Frame::Reset(5, REFUSED),
+            client.next().await.unwrap().unwrap()
+        );
+
+        // and a refused tag can be tried again once there is room
+        drop(first);
+
+        assert_eq!(
+            Frame::Reset(1, CANCEL),
+            client.next().await.unwrap().unwrap()
+        );
+
+        client.send(Frame::Data(3, "c".to_owned())).await.unwrap();
+
+        let mut second = lanes.accept().await.unwrap();
+
+        assert_eq!(Some("c".to_owned()), second.receiver().recv().await);
+    }
+
+    #[tokio::test]
+    async fn transport_lifecycle_test() {
+        let (client, server) = tokio::io::duplex(64);
+        let (transport, mut lanes) =
+            Mux::over(server, TestCodec::default(), 8, 4);
+        let driver = tokio::spawn(transport.run());
+        let mut client = Framed::new(client, TestCodec::default());
+
+        macro_rules! exchange {
+            ([$($send:expr),*] => [$($recv:expr),*]) => {
+                $(client.send($send).await.unwrap();)*
+                $(assert_eq!($recv, client.next().await.unwrap().unwrap());)*
+            };
+        }
+
+        let acceptor = tokio::spawn(async move {
+            // half-closed by the remote end, then by us
+            let (mut tx, mut rx) = lanes.accept().await.unwrap().split();
+
+            assert_eq!(Some("a".to_owned()), rx.recv().await);
+            assert_eq!(None, rx.recv().await);
+
+            tx.send("A".to_owned()).await.unwrap();
+            drop((tx, rx));
+
+            // refused by dropping it
+            drop(lanes.accept().await.unwrap());
+
+            lanes
+        });
+
+        exchange!([Frame::Data(1, "a".to_owned()), Frame::Fin(1)] => [
+            Frame::Data(1, "A".to_owned()),
+            Frame::Fin(1)
+        ]);
+        exchange!([Frame::Open(2)] => [Frame::Reset(2, CANCEL)]);
+        exchange!([Frame::Ping(42)] => [Frame::Pong(42)]);
+
+        // stop accepting lanes
+        drop(acceptor.await.unwrap());
+
+        exchange!([] => [Frame::GoAway(NO_ERROR)]);
+        exchange!([Frame::Data(3, "x".to_owned())] => [Frame::Reset(3, REFUSED)]);
+
+        // an unknown frame type is a protocol error
+        client.get_mut().write_all(&[1, 0xFF]).await.unwrap();
+
+        assert_eq!(
+            Frame::GoAway(PROTOCOL_ERROR),
+            client.next().await.unwrap().unwrap()
+        );
+        assert_eq!(
+            io::ErrorKind::InvalidData,
+            driver.await.unwrap().unwrap_err().kind()
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use super::frame::{Frame, CANCEL};
+use crate::lane::Signal;
+use crate::Key;
+
+/// The state of a single lane, as seen by the transport.
+#[derive(Debug)]
+pub(super) struct LaneState {
+    local_fin: bool,
+    remote_fin: bool,
+    reset: bool,
+    // the credit the remote end can still spend on the lane
+    window: u32,
+}
+
+/// The state shared between the reading and writing halves of a transport.
+#[derive(Debug)]
+pub(super) struct State<T> {
+    lanes: HashMap<T, LaneState>,
+    window: u32,
+    refusing: bool,
+    remote_go_away: bool,
+}
+
+/// What to do with an inbound data frame.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum Inbound {
+    /// The frame belongs to a known lane.
+    Existing,
+    /// The frame opens a new lane.
+    New,
+    /// The lane was refused, and must be reset.
+    Refused,
+    /// The frame belongs to a finished lane, and must be dropped.
+    Stale,
+    /// The frame costs more than the credit the remote end was granted, and
+    /// the lane must be reset.
+    Overrun,
+}
+
+impl LaneState {
+    #[inline]
+    const fn new(window: u32) -> Self {
+        Self {
+            local_fin: false,
+            remote_fin: false,
+            reset: false,
+            window,
+        }
+    }
+}
+
+impl<T: Key> State<T> {
+    /// Creates a new, locked-able instance of [`State`].
+    ///
+    /// # Parameters
+    /// * `window` - The credit the remote end can spend on each lane before it
+    ///   is granted more.
+    #[inline]
+    pub(super) fn new(window: u32) -> Mutex<Self> {
+        Mutex::new(Self {
+            lanes: HashMap::new(),
+            window,
+            refusing: false,
+            remote_go_away: false,
+        })
+    }
+
+    /// Locks `state`, ignoring poisoning as the state is always consistent.
+    #[inline]
+    pub(super) fn lock(state: &Mutex<Self>) -> MutexGuard<'_, Self> {
+        state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Gets whether new lanes from the remote end are refused or not.
+    #[inline]
+    pub(super) const fn is_refusing(&self) -> bool {
+        self.refusing
+    }
+
+    /// Refuses any new lanes from the remote end.
+    ///
+    /// # Returns
+    /// Whether lanes were not already refused.
+    #[inline]
+    pub(super) fn refuse(&mut self) -> bool {
+        !std::mem::replace(&mut self.refusing, true)
+    }
+
+    /// Records that the remote end went away, and will not accept any new
+    /// lanes.
+    #[inline]
+    pub(super) fn remote_go_away(&mut self) {
+        self.remote_go_away = true;
+    }
+
+    /// Classifies an inbound data or open frame for `tag`, registering the
+    /// lane if it is new and accepted, and spending `cost` out of the credit
+    /// the remote end was granted for it.
+    pub(super) fn inbound(&mut self, tag: &T, cost: u32) -> Inbound {
+        match self.lanes.get_mut(tag) {
+            | Some(lane) if lane.remote_fin || lane.reset => Inbound::Stale,
+            | Some(lane) => match lane.window.checked_sub(cost) {
+                | Some(window) => {
+                    lane.window = window;
+                    Inbound::Existing
+                }
+                | None => Inbound::Overrun,
+            },
+            | None if self.refusing => Inbound::Refused,
+            | None if cost > self.window => Inbound::Overrun,
+            | None => {
+                let lane = LaneState::new(self.window - cost);
+
+                self.lanes.insert(tag.clone(), lane);
+                Inbound::New
+            }
+        }
+    }
+
+    /// Forgets a new lane that was refused locally, e.g. by admission control,
+    /// so that it is classified as new again.
+    pub(super) fn forget(&mut self, tag: &T) {
+        self.lanes.remove(tag);
+    }
+
+    /// Records that the remote end half-closed the lane.
+    pub(super) fn remote_fin(&mut self, tag: &T) {
+        self.update(tag, |lane| lane.remote_fin = true);
+    }
+
+    /// Records that the lane was reset by either end, so that any further
+    /// frames for it are dropped.
+    pub(super) fn reset(&mut self, tag: &T) {
+        self.update(tag, |lane| lane.reset = true);
+    }
+
+    /// Gets the frames to write for an outgoing value.
+    ///
+    /// Lanes that were not seen before are opened first, and values sent
+    /// through reset lanes are dropped.
+    ///
+    /// # Returns
+    /// * [`Some(frames)`] - The frames to write.
+    /// * [`None`] - If the lane is new but the remote end went away, in which
+    ///   case the lane is considered reset and must be closed locally.
+    pub(super) fn outgoing<V>(
+        &mut self,
+        tag: T,
+        value: V,
+    ) -> Option<Vec<Frame<T, V>>> {
+        let frames = match self.lanes.get(&tag) {
+            | Some(lane) if lane.reset => vec![],
+            | Some(_) => vec![Frame::Data(tag, value)],
+            | None if self.remote_go_away => {
+                let lane = LaneState {
+                    reset: true,
+                    ..LaneState::new(self.window)
+                };
+
+                self.lanes.insert(tag, lane);
+
+                return None;
+            }
+            | None => {
+                self.lanes.insert(tag.clone(), LaneState::new(self.window));
+                vec![Frame::Open(tag.clone()), Frame::Data(tag, value)]
+            }
+        };
+
+        Some(frames)
+    }
+
+    /// Gets the frame to write, if any, in response to a local lane signal.
+    pub(super) fn signal<V>(
+        &mut self,
+        signal: Signal<T>,
+    ) -> Option<Frame<T, V>> {
+        match signal {
+            | Signal::TxClosed(tag, _) => {
+                let lane = self.lanes.get_mut(&tag)?;
+                let frame = (!lane.reset).then(|| Frame::Fin(tag.clone()));
+
+                lane.local_fin = true;
+                self.prune(&tag);
+
+                frame
+            }
+            | Signal::RxClosed(tag) => {
+                let lane = self.lanes.get_mut(&tag)?;
+
+                if lane.remote_fin || lane.reset {
+                    return None;
+                }
+
+                lane.reset = true;
+                self.prune(&tag);
+
+                Some(Frame::Reset(tag, CANCEL))
+            }
+            | Signal::Consumed(tag, credit) => {
+                let lane = self.lanes.get_mut(&tag)?;
+
+                // no more values will be received anyway
+                if lane.remote_fin || lane.reset {
+                    return None;
+                }
+
+                lane.window = lane.window.saturating_add(credit);
+
+                Some(Frame::WindowUpdate(tag, credit))
+            }
+        }
+    }
+
+    #[inline]
+    fn update<F: FnOnce(&mut LaneState)>(&mut self, tag: &T, f: F) {
+        if let Some(lane) = self.lanes.get_mut(tag) {
+            f(lane);
+            self.prune(tag);
+        }
+    }
+
+    /// Forgets the lane once both of its directions are done.
+    #[inline]
+    fn prune(&mut self, tag: &T) {
+        let done = self.lanes.get(tag).is_some_and(|lane| {
+            lane.local_fin && (lane.remote_fin || lane.reset)
+        });
+
+        if done {
+            self.lanes.remove(tag);
+        }
+    }
+}
@@ -0,0 +1,549 @@
+//! Interoperability with [yamux](https://github.com/hashicorp/yamux/blob/master/spec.md)
+//! peers.
+//!
+//! Each yamux stream maps to a lane whose tag is the stream ID, and whose
+//! values are the chunks of bytes carried by the stream's data frames. Note
+//! that yamux streams are byte streams, so peers are free to split or
+//! coalesce the values that are written to them.
+
+use std::collections::{HashSet, VecDeque};
+use std::io;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::mpsc;
+use tokio_util::codec::{Decoder, Encoder};
+
+use super::{Frame, Transport, CANCEL};
+use crate::codec::invalid_data;
+use crate::lane::Hooks;
+use crate::{Incoming, Mux};
+
+/// The initial window of each yamux stream, in bytes.
+pub const INITIAL_WINDOW: u32 = 256 * 1024;
+
+const VERSION: u8 = 0;
+const HEADER_LEN: usize = 12;
+
+const DATA: u8 = 0;
+const WINDOW_UPDATE: u8 = 1;
+const PING: u8 = 2;
+const GO_AWAY: u8 = 3;
+
+const SYN: u16 = 0x1;
+const ACK: u16 = 0x2;
+const FIN: u16 = 0x4;
+const RST: u16 = 0x8;
+
+/// A [`Decoder`] and [`Encoder`] of [`Frame`]s in the yamux wire format.
+///
+/// Frames map to yamux frames as follows:
+/// * [`Frame::Data`] is a data frame.
+/// * [`Frame::Open`] is a window update with the `SYN` flag, or with the `ACK`
+///   flag if the stream was opened by the remote end.
+/// * [`Frame::Fin`] and [`Frame::Reset`] are window updates with the `FIN` and
+///   `RST` flags. As yamux resets carry no error code, decoded resets always
+///   carry [`CANCEL`].
+/// * [`Frame::WindowUpdate`] is a window update, in bytes.
+/// * [`Frame::Ping`] and [`Frame::Pong`] are ping frames with the `SYN` and
+///   `ACK` flags.
+/// * [`Frame::GoAway`] is a go away frame, whose error codes coincide with
+///   [`NO_ERROR`](super::NO_ERROR) and
+///   [`PROTOCOL_ERROR`](super::PROTOCOL_ERROR).
+#[derive(Debug, Clone)]
+pub struct YamuxCodec {
+    max_frame_len: usize,
+    pending: VecDeque<Frame<u32, Bytes>>,
+    unacked: HashSet<u32>,
+}
+
+impl YamuxCodec {
+    /// Creates a new instance of [`YamuxCodec`], that accepts data frames of
+    /// up to 8 MiB.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_max_frame_len(8 * 1024 * 1024)
+    }
+
+    /// Creates a new instance of [`YamuxCodec`] with a limit on the length
+    /// of decoded data frames.
+    ///
+    /// # Parameters
+    /// * `max_frame_len` - The maximum payload length of a data frame.
+    #[inline]
+    pub fn with_max_frame_len(max_frame_len: usize) -> Self {
+        Self {
+            max_frame_len,
+            pending: VecDeque::new(),
+            unacked: HashSet::new(),
+        }
+    }
+
+    /// Gets the maximum payload length of a decoded data frame.
+    #[inline]
+    pub const fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Gets the flags to set on an outgoing frame of the given stream, which
+    /// acknowledge the stream if it was opened by the remote end.
+    #[inline]
+    fn flags(&mut self, id: u32) -> u16 {
+        if self.unacked.remove(&id) {
+            ACK
+        } else {
+            0
+        }
+    }
+}
+
+impl Default for YamuxCodec {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Encoder<Frame<u32, Bytes>> for YamuxCodec {
+    type Error = io::Error;
+
+    fn encode(
+        &mut self,
+        frame: Frame<u32, Bytes>,
+        dst: &mut BytesMut,
+    ) -> io::Result<()> {
+        let (ty, flags, id, len) = match frame {
+            | Frame::Data(id, ref value) => {
+                let len = u32::try_from(value.len()).map_err(invalid_data)?;
+
+                (DATA, self.flags(id), id, len)
+            }
+            | Frame::Open(id) => match self.flags(id) {
+                | 0 => (WINDOW_UPDATE, SYN, id, 0),
+                | flags => (WINDOW_UPDATE, flags, id, 0),
+            },
+            | Frame::Fin(id) => (WINDOW_UPDATE, self.flags(id) | FIN, id, 0),
+            | Frame::Reset(id, _) => {
+                self.unacked.remove(&id);
+                (WINDOW_UPDATE, RST, id, 0)
+            }
+            | Frame::WindowUpdate(id, delta) => {
+                (WINDOW_UPDATE, self.flags(id), id, delta)
+            }
+            | Frame::Ping(opaque) => (PING, SYN, 0, opaque),
+            | Frame::Pong(opaque) => (PING, ACK, 0, opaque),
+            | Frame::GoAway(code) => (GO_AWAY, 0, 0, code),
+        };
+
+        dst.reserve(HEADER_LEN);
+        dst.put_u8(VERSION);
+        dst.put_u8(ty);
+        dst.put_u16(flags);
+        dst.put_u32(id);
+        dst.put_u32(len);
+
+        if let Frame::Data(_, value) = frame {
+            dst.extend_from_slice(&value);
+        }
+
+        Ok(())
+    }
+}
+
+impl Decoder for YamuxCodec {
+    type Error = io::Error;
+    type Item = Frame<u32, Bytes>;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Self::Item>> {
+        // a single yamux frame may carry several frames, e.g. data that both
+        // opens and half-closes a stream
+        while self.pending.is_empty() {
+            if src.len() < HEADER_LEN {
+                src.reserve(HEADER_LEN - src.len());
+                return Ok(None);
+            }
+
+            let mut header = &src[..HEADER_LEN];
+            let version = header.get_u8();
+            let ty = header.get_u8();
+            let flags = header.get_u16();
+            let id = header.get_u32();
+            let len = header.get_u32();
+
+            if version != VERSION {
+                return Err(invalid_data(format!(
+                    "unsupported version {version}"
+                )));
+            }
+
+            let body_len = if ty == DATA { len as usize } else { 0 };
+
+            if body_len > self.max_frame_len {
+                return Err(invalid_data(format!(
+                    "frame of length {body_len} is too large"
+                )));
+            }
+
+            if src.len() < HEADER_LEN + body_len {
+                src.reserve(HEADER_LEN + body_len - src.len());
+                return Ok(None);
+            }
+
+            src.advance(HEADER_LEN);
+
+            let body = src.split_to(body_len).freeze();
+
+            match ty {
+                | DATA | WINDOW_UPDATE if id == 0 => {
+                    return Err(invalid_data("stream frame with stream ID 0"));
+                }
+                | DATA | WINDOW_UPDATE => {
+                    if flags & SYN != 0 {
+                        self.unacked.insert(id);
+                        self.pending.push_back(Frame::Open(id));
+                    }
+
+                    if !body.is_empty() {
+                        self.pending.push_back(Frame::Data(id, body));
+                    }
+
+                    if ty == WINDOW_UPDATE && len > 0 {
+                        self.pending.push_back(Frame::WindowUpdate(id, len));
+                    }
+
+                    if flags & FIN != 0 {
+                        self.pending.push_back(Frame::Fin(id));
+                    }
+
+                    if flags & RST != 0 {
+                        self.unacked.remove(&id);
+                        self.pending.push_back(Frame::Reset(id, CANCEL));
+                    }
+                }
+                | PING if flags & SYN != 0 => {
+                    self.pending.push_back(Frame::Ping(len));
+                }
+                | PING => self.pending.push_back(Frame::Pong(len)),
+                | GO_AWAY => self.pending.push_back(Frame::GoAway(len)),
+                | ty => {
+                    return Err(invalid_data(format!(
+                        "unknown frame type {ty}"
+                    )))
+                }
+            }
+        }
+
+        Ok(self.pending.pop_front())
+    }
+}
+
+impl Mux<u32, Bytes> {
+    /// Creates a new [`Mux`](crate::mux::Mux) that speaks yamux over `io`,
+    /// with each lane mapped to the yamux stream whose ID is the lane's tag.
+    ///
+    /// Per the yamux specification, the end that initiated the connection
+    /// should only open lanes with odd tags, and the other end with even
+    /// ones (see [`Transport::with_allocator`]). Lanes opened by the remote
+    /// end are acknowledged once accepted.
+    ///
+    /// Streams are flow controlled by bytes with windows of
+    /// [`INITIAL_WINDOW`] bytes, so each lane buffers up to a window's worth
+    /// of bytes, however many data frames they come in. A remote end that
+    /// sends past its window has the stream reset. Values that do not fit in
+    /// the window the remote end granted are split, and sent in several data
+    /// frames as the window allows (see [`LaneTx::send`](crate::LaneTx::send)).
+    ///
+    /// This is only available when the `yamux` feature is enabled.
+    ///
+    /// # Parameters
+    /// * `io` - The underlying byte stream.
+    /// * `buf` - The buffer size of each lane's outgoing messages queue.
+    /// * `lane_buf` - The number of streams opened by the remote end that can
+    ///   wait to be accepted, past which they are reset.
+    ///
+    /// # Returns
+    /// A [`Transport`] that must be driven for any I/O to happen, and a
+    /// receiver that yields lanes as they are created by the remote end.
+    pub fn over_yamux<IO>(
+        io: IO,
+        buf: usize,
+        lane_buf: usize,
+    ) -> (Transport<u32, Bytes, IO, YamuxCodec>, Incoming<u32, Bytes>)
+    where
+        IO: AsyncRead + AsyncWrite + Unpin,
+    {
+        let (signals_tx, signals) = mpsc::unbounded_channel();
+        let hooks = Hooks::new(signals_tx, INITIAL_WINDOW)
+            .with_cost(|value: &Bytes| {
+                value.len().try_into().unwrap_or(u32::MAX)
+            })
+            .with_split(|value: &mut Bytes, len| value.split_to(len as usize));
+
+        // each data frame carries at least a byte, so lanes never hold more
+        // values than there are bytes in a window
+        Transport::new(
+            io,
+            YamuxCodec::new(),
+            buf,
+            INITIAL_WINDOW as usize,
+            lane_buf,
+            hooks,
+            signals,
+            true,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{SinkExt, StreamExt};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_util::codec::Framed;
+
+    use super::*;
+    use crate::transport::NO_ERROR;
+
+    // what a hashicorp/yamux client writes to open a stream, write to it,
+    // ping the remote end, half-close the stream, then go away
+    const CLIENT: &[&[u8]] = &[
+        &[0, 1, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0],
+        &[
+            0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 5, b'h', b'e', b'l', b'l', b'o',
+        ],
+        &[0, 2, 0, 1, 0, 0, 0, 0, 0, 0, 0, 42],
+        &[0, 1, 0, 4, 0, 0, 0, 1, 0, 0, 0, 0],
+        &[0, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+    ];
+
+    // what a hashicorp/yamux server answers with when echoing the stream, in
+    // response to each of the client's frames
+    const SERVER: &[&[u8]] = &[
+        &[0, 1, 0, 2, 0, 0, 0, 1, 0, 0, 0, 0],
+        &[
+            0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 5, b'h', b'e', b'l', b'l', b'o',
+        ],
+        &[0, 2, 0, 2, 0, 0, 0, 0, 0, 0, 0, 42],
+        &[0, 1, 0, 4, 0, 0, 0, 1, 0, 0, 0, 0],
+        &[],
+    ];
+
+    #[test]
+    fn yamux_codec_test() {
+        let mut codec = YamuxCodec::new();
+        let mut buf = BytesMut::from(&CLIENT.concat()[..]);
+
+        let frames = [
+            Frame::Open(1),
+            Frame::Data(1, Bytes::from_static(b"hello")),
+            Frame::Ping(42),
+            Frame::Fin(1),
+            Frame::GoAway(NO_ERROR),
+        ];
+
+        for frame in frames {
+            assert_eq!(Some(frame), codec.decode(&mut buf).unwrap());
+        }
+
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+
+        let frames = [
+            Frame::Open(1),
+            Frame::Data(1, Bytes::from_static(b"hello")),
+            Frame::Pong(42),
+            Frame::Fin(1),
+        ];
+
+        for frame in frames {
+            codec.encode(frame, &mut buf).unwrap();
+        }
+
+        assert_eq!(SERVER.concat(), buf);
+
+        // data that opens and resets a stream at once
+        buf.clear();
+        buf.extend_from_slice(&[0, 0, 0, 9, 0, 0, 0, 2, 0, 0, 0, 1, b'x']);
+
+        let frames = [
+            Frame::Open(2),
+            Frame::Data(2, Bytes::from_static(b"x")),
+            Frame::Reset(2, CANCEL),
+        ];
+
+        for frame in frames {
+            assert_eq!(Some(frame), codec.decode(&mut buf).unwrap());
+        }
+
+        // an unsupported version is a protocol error
+        buf.extend_from_slice(&[1; HEADER_LEN]);
+        assert!(codec.decode(&mut buf).is_err());
+    }
+
+    #[tokio::test]
+    async fn yamux_transport_test() {
+        let (mut client, server) = tokio::io::duplex(1024);
+        let (transport, mut lanes) = Mux::over_yamux(server, 8, 8);
+        let driver = tokio::spawn(transport.run());
+
+        tokio::spawn(async move {
+            while let Some(lane) = lanes.accept().await {
+                tokio::spawn(async move {
+                    let (mut tx, mut rx) = lane.split();
+
+                    while let Some(value) = rx.recv().await {
+                        tx.send(value).await.unwrap();
+                    }
+                });
+            }
+        });
+
+        for (sent, expected) in CLIENT.iter().zip(SERVER) {
+            let mut buf = vec![0; expected.len()];
+
+            client.write_all(sent).await.unwrap();
+            client.read_exact(&mut buf).await.unwrap();
+
+            assert_eq!(expected, &buf);
+        }
+
+        drop(client);
+
+        driver.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn yamux_open_test() {
+        const SYN_3: &[u8] = &[0, 1, 0, 1, 0, 0, 0, 3, 0, 0, 0, 0];
+        const RST_3: &[u8] = &[0, 1, 0, 8, 0, 0, 0, 3, 0, 0, 0, 0];
+        const SYN_5: &[u8] = &[0, 1, 0, 1, 0, 0, 0, 5, 0, 0, 0, 0];
+        const ACK_5: &[u8] = &[0, 1, 0, 2, 0, 0, 0, 5, 0, 0, 0, 0];
+        const RST_5: &[u8] = &[0, 1, 0, 8, 0, 0, 0, 5, 0, 0, 0, 0];
+        const SYN_7: &[u8] = &[0, 1, 0, 1, 0, 0, 0, 7, 0, 0, 0, 0];
+        const ACK_7: &[u8] = &[0, 1, 0, 2, 0, 0, 0, 7, 0, 0, 0, 0];
+
+        let (mut client, server) = tokio::io::duplex(1024);
+        let (transport, mut lanes) = Mux::over_yamux(server, 8, 1);
+        let driver = tokio::spawn(transport.run());
+        let mut buf = [0; HEADER_LEN];
+
+        client.write_all(CLIENT[0]).await.unwrap();
+        client.read_exact(&mut buf).await.unwrap();
+        assert_eq!(SERVER[0], buf);
+
+        // streams are refused while the backlog is full
+        client.write_all(SYN_3).await.unwrap();
+        client.read_exact(&mut buf).await.unwrap();
+        assert_eq!(RST_3, buf);
+
+        // and only acknowledged once they make it to the backlog
+        let _first = lanes.accept().await.unwrap();
+
+        client.write_all(SYN_5).await.unwrap();
+        client.read_exact(&mut buf).await.unwrap();
+        assert_eq!(ACK_5, buf);
+
+        // values larger than the window are sent as the window allows
+        let (mut tx, _rx) = lanes.accept().await.unwrap().split();
+        let value = Bytes::from(vec![b'x'; INITIAL_WINDOW as usize + 1]);
+        let sender =
+            tokio::spawn(async move { tx.send(value).await.map(|()| tx) });
+        let mut body = vec![0; INITIAL_WINDOW as usize];
+
+        client.read_exact(&mut buf).await.unwrap();
+        assert_eq!([0, 0, 0, 0, 0, 0, 0, 5, 0, 4, 0, 0], buf);
+        client.read_exact(&mut body).await.unwrap();
+
+        client
+            .write_all(&[0, 1, 0, 0, 0, 0, 0, 5, 0, 0, 0, 1])
+            .await
+            .unwrap();
+        let _tx = sender.await.unwrap().unwrap();
+
+        let mut buf = [0; HEADER_LEN + 1];
+
+        client.read_exact(&mut buf).await.unwrap();
+        assert_eq!([0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 1, b'x'], buf);
+
+        // lanes hold a window's worth of bytes, however they are framed
+        for _ in 0..64 {
+            client
+                .write_all(&[0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 1, b'y'])
+                .await
+                .unwrap();
+        }
+
+        let mut buf = [0; HEADER_LEN];
+
+        client.write_all(SYN_7).await.unwrap();
+        client.read_exact(&mut buf).await.unwrap();
+        assert_eq!(ACK_7, buf);
+
+        // while sending past the window resets the stream
+        let mut frame = vec![0, 0, 0, 0, 0, 0, 0, 5];
+
+        frame.extend_from_slice(&INITIAL_WINDOW.to_be_bytes());
+        frame.resize(HEADER_LEN + INITIAL_WINDOW as usize, b'z');
+        client.write_all(&frame).await.unwrap();
+        client.read_exact(&mut buf).await.unwrap();
+        assert_eq!(RST_5, buf);
+
+        drop(client);
+        driver.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn yamux_flow_control_test() {
+        let (client, server) = tokio::io::duplex(1024);
+        let (transport, mut lanes) = Mux::over_yamux(server, 8, 8);
+        let driver = tokio::spawn(transport.run());
+        let mut client = Framed::new(client, YamuxCodec::new());
+
+        let handler = tokio::spawn(async move {
+            let (mut tx, mut rx) = lanes.accept().await.unwrap().split();
+            let chunk = Bytes::from(vec![0; INITIAL_WINDOW as usize / 4]);
+
+            for _ in 0..5 {
+                tx.send(chunk.clone()).await.unwrap();
+            }
+
+            let value = rx.recv().await.unwrap();
+
+            assert_eq!(INITIAL_WINDOW as usize / 2, value.len());
+
+            (lanes, tx, rx)
+        });
+
+        client.send(Frame::Open(1)).await.unwrap();
+
+        // only a window's worth of bytes is sent without credit
+        for _ in 0..4 {
+            let frame = client.next().await.unwrap().unwrap();
+
+            assert!(matches!(frame, Frame::Data(1, _)));
+        }
+
+        client
+            .send(Frame::WindowUpdate(1, INITIAL_WINDOW / 4))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            client.next().await.unwrap().unwrap(),
+            Frame::Data(1, _)
+        ));
+
+        // credit is granted back in bytes once half of the window is consumed
+        let chunk = Bytes::from(vec![0; INITIAL_WINDOW as usize / 2]);
+
+        client.send(Frame::Data(1, chunk)).await.unwrap();
+
+        assert_eq!(
+            Frame::WindowUpdate(1, INITIAL_WINDOW / 2),
+            client.next().await.unwrap().unwrap()
+        );
+
+        drop(client);
+
+        driver.await.unwrap().unwrap();
+        drop(handler.await.unwrap());
+    }
+}
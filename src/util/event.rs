@@ -0,0 +1,84 @@
+use std::fmt;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use futures::Stream;
+use tokio_util::sync::ReusableBoxFuture;
+
+use crate::{LaneEvent, LaneEvents};
+
+/// The future receiving the next event, which gives the receiver back along
+/// with it.
+type Next<T> =
+    ReusableBoxFuture<'static, (Option<LaneEvent<T>>, LaneEvents<T>)>;
+
+/// An adapter for [`LaneEvents<T>`] that implements
+/// [`Stream`](futures::Stream).
+pub struct LaneEventStream<T>(Next<T>);
+
+impl<T> LaneEventStream<T>
+where
+    T: Clone + Send + 'static,
+{
+    /// Creates a new [`LaneEventStream`] from the given [`LaneEvents<T>`].
+    #[inline]
+    pub fn new(events: LaneEvents<T>) -> Self {
+        Self(ReusableBoxFuture::new(next(events)))
+    }
+}
+
+impl<T> fmt::Debug for LaneEventStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LaneEventStream").finish_non_exhaustive()
+    }
+}
+
+impl<T> Stream for LaneEventStream<T>
+where
+    T: Clone + Send + 'static,
+{
+    type Item = LaneEvent<T>;
+
+    #[inline]
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let (event, events) = ready!(self.0.poll(cx));
+
+        self.0.set(next(events));
+
+        Poll::Ready(event)
+    }
+}
+
+impl<T> From<LaneEvents<T>> for LaneEventStream<T>
+where
+    T: Clone + Send + 'static,
+{
+    #[inline(always)]
+    fn from(value: LaneEvents<T>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> LaneEvents<T>
+where
+    T: Clone + Send + 'static,
+{
+    /// Converts this receiver into a [`LaneEventStream<T>`].
+    ///
+    /// This is only available when the `util` feature is enabled.
+    #[inline]
+    pub fn into_stream(self) -> LaneEventStream<T> {
+        self.into()
+    }
+}
+
+async fn next<T: Clone>(
+    mut events: LaneEvents<T>,
+) -> (Option<LaneEvent<T>>, LaneEvents<T>) {
+    let event = events.recv().await;
+
+    (event, events)
+}
@@ -0,0 +1,18 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+
+use crate::{Incoming, Key, Lane};
+
+impl<T: Key, V> Stream for Incoming<T, V> {
+    type Item = Lane<T, V>;
+
+    #[inline(always)]
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.poll_accept(cx)
+    }
+}
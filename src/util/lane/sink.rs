@@ -1,9 +1,11 @@
 use std::pin::Pin;
-use std::task::{Context, Poll};
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
 
 use futures::Sink;
-use tokio_util::sync::{PollSendError, PollSender};
+use tokio_util::sync::{PollSemaphore, PollSendError, PollSender};
 
+use crate::lane::{TxCloser, TxGuard};
 use crate::{Key, LaneTx};
 
 pin_project_lite::pin_project! {
@@ -11,10 +13,17 @@ pin_project_lite::pin_project! {
     /// want a type that implements [`Sink<(T, V)>`], as the current
     /// [`LaneTx<T, V>`] *does not* currently support polling.
     #[derive(Debug)]
-    pub struct LaneSink<T, V> {
+    pub struct LaneSink<T: Key, V> {
         #[pin]
         inner: PollSender<(T, V)>,
         tag: T,
+        credits: Option<PollSemaphore>,
+        guard: Option<Arc<TxGuard<T, V>>>,
+        _closer: Option<Arc<TxCloser<T>>>,
+        pending: Option<V>,
+        // the part split off the pending value that was paid for, if any
+        part: Option<V>,
+        charged: bool,
     }
 }
 
@@ -26,11 +35,19 @@ where
     /// Constructs a new [`LaneTx<T, V>`] instance from the given sender.
     #[inline]
     pub fn new(sender: LaneTx<T, V>) -> Self {
-        let (tag, sender) = sender.into_inner();
+        let (tag, sender, guard, closer) = sender.into_inner();
 
         Self {
             tag,
             inner: PollSender::new(sender),
+            credits: guard
+                .as_ref()
+                .map(|guard| PollSemaphore::new(guard.credits().clone())),
+            guard,
+            _closer: closer,
+            pending: None,
+            part: None,
+            charged: false,
         }
     }
 
@@ -62,36 +79,111 @@ where
 
     #[inline(always)]
     fn poll_ready(
-        self: Pin<&mut Self>,
+        mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Result<(), Self::Error>> {
-        self.project().inner.poll_ready(cx)
+        ready!(self.as_mut().poll_pending(cx))?;
+
+        let this = self.project();
+
+        match this.credits {
+            // the next value is buffered until it is paid for
+            | Some(_) => Poll::Ready(Ok(())),
+            | None => this.inner.poll_ready(cx),
+        }
     }
 
     #[inline(always)]
     fn start_send(self: Pin<&mut Self>, item: V) -> Result<(), Self::Error> {
         let tag = self.tag.clone();
+        let this = self.project();
+
+        if this.credits.is_some() {
+            *this.pending = Some(item);
+            return Ok(());
+        }
 
-        self.project().inner.start_send((tag, item))
+        this.inner.start_send((tag, item))
     }
 
     #[inline(always)]
     fn poll_flush(
-        self: Pin<&mut Self>,
+        mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Result<(), Self::Error>> {
+        ready!(self.as_mut().poll_pending(cx))?;
+
         self.project().inner.poll_flush(cx)
     }
 
     #[inline(always)]
     fn poll_close(
-        self: Pin<&mut Self>,
+        mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Result<(), Self::Error>> {
+        ready!(self.as_mut().poll_pending(cx))?;
+
         self.project().inner.poll_close(cx)
     }
 }
 
+impl<T, V> LaneSink<T, V>
+where
+    T: Key + Send + 'static,
+    V: Send + 'static,
+{
+    /// Sends the buffered value, if any, once there is enough credit for it.
+    ///
+    /// Values that can be split are sent in parts as credit is granted (see
+    /// [`LaneTx::send`]).
+    fn poll_pending(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), PollSendError<(T, V)>>> {
+        let mut this = self.project();
+        let (Some(credits), Some(guard)) = (this.credits, this.guard) else {
+            return Poll::Ready(Ok(()));
+        };
+
+        loop {
+            let Some(ref mut value) = this.pending else {
+                return Poll::Ready(Ok(()));
+            };
+
+            if !*this.charged {
+                let permit = match guard.charge(value) {
+                    | Some(credit) => {
+                        ready!(credits.poll_acquire_many(cx, credit))
+                            .map(|permit| (permit, credit))
+                    }
+                    | None => None,
+                };
+
+                match permit {
+                    | Some((permit, credit)) => {
+                        permit.forget();
+                        *this.charged = true;
+                        *this.part = guard.split(value, credit);
+                    }
+                    // the lane was reset, or the value can never be sent, fail
+                    // as if it was closed
+                    | None => this.inner.close(),
+                }
+            }
+
+            ready!(this.inner.as_mut().poll_ready(cx))?;
+
+            let item = match this.part.take() {
+                | Some(part) => part,
+                | None => this.pending.take().expect("a value to be pending"),
+            };
+
+            *this.charged = false;
+            this.inner.as_mut().start_send((this.tag.clone(), item))?;
+        }
+    }
+}
+
 impl<T, V> From<LaneTx<T, V>> for LaneSink<T, V>
 where
     T: Key + Send + 'static,
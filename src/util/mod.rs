@@ -1 +1,3 @@
+pub mod event;
+mod incoming;
 pub mod lane;